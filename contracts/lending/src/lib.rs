@@ -1,7 +1,62 @@
 
 
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error, Address, Env, String, Vec,
+};
+
+/// Salary stream as stored by the salary streaming contract
+#[derive(Clone)]
+#[contracttype]
+pub struct SalaryStream {
+    pub id: u32,
+    pub employer: Address,
+    pub employee: Address,
+    pub total_amount: i128,
+    pub rate_per_second: i128,
+    pub start_time: u64,
+    pub duration_seconds: u64,
+    pub withdrawn_amount: i128,
+    pub is_active: bool,
+    pub is_paused: bool,
+}
+
+/// Interface of the salary streaming contract used for collateral valuation and recovery
+#[contractclient(name = "SalaryStreamingClient")]
+pub trait SalaryStreamingInterface {
+    fn get_stream(env: Env, stream_id: u32) -> SalaryStream;
+    fn calculate_available(env: Env, stream_id: u32) -> i128;
+    fn place_lien(env: Env, lender: Address, stream_id: u32, amount: i128);
+    fn collect_lien(env: Env, lender: Address, stream_id: u32, max_amount: i128) -> i128;
+    fn release_lien(env: Env, lender: Address, stream_id: u32);
+    fn adjust_lien(env: Env, lender: Address, stream_id: u32, remaining: i128);
+}
+
+/// Repayment behaviour as recorded by the work profile contract
+#[derive(Clone)]
+#[contracttype]
+pub enum CreditEvent {
+    OnTimePayment,
+    LatePayment,
+    Default,
+    Payoff,
+}
+
+/// Interface of the work profile contract that scores borrowers on their credit history
+#[contractclient(name = "WorkProfileClient")]
+pub trait WorkProfileInterface {
+    fn report_credit_event(env: Env, reporter: Address, employee: Address, event: CreditEvent);
+    fn is_profile_verified(env: Env, employee: Address) -> bool;
+    fn get_employee_risk_tier(env: Env, employee: Address) -> u32;
+    fn is_profile_disputed(env: Env, employee: Address) -> bool;
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LendingError {
+    EmployerExposureExceeded = 1,
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -10,6 +65,9 @@ pub enum LoanStatus {
     Approved,
     Repaid,
     Defaulted,
+    Refinanced,
+    WrittenOff,
+    AtRisk, // Borrower's employment or salary stream ended
 }
 
 #[derive(Clone)]
@@ -24,6 +82,43 @@ pub struct Loan {
     pub created_at: u64,
     pub repaid_amount: i128,
     pub collateral_stream_id: u32,
+    pub term_months: u32,
+    pub installment_amount: i128,
+    pub scheduled_installment: Option<i128>, // Installment before acceleration, set while at risk
+    pub previous_loan_id: Option<u32>, // Set when this loan refinanced an earlier one
+    pub guarantor: Option<Address>,
+    pub recovered_amount: i128, // Recovered from the collateral stream after default
+    pub accrued_interest: i128, // Unpaid interest accrued so far
+    pub interest_paid: i128,
+    pub last_accrual: u64,
+    pub written_off_amount: i128,
+    pub origination_fee: i128, // Deducted from the disbursed amount at approval
+    pub approved_at: u64,      // Origination time, 0 while pending
+    pub employer: Address,     // Employer funding the collateral stream
+    pub product_id: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum GuarantorCollateral {
+    Stream(u32), // Guarantor's own salary stream ID
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct GuarantorPledge {
+    pub guarantor: Address,
+    pub collateral: GuarantorCollateral,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Guarantee {
+    pub loan_id: u32,
+    pub guarantor: Address,
+    pub collateral: GuarantorCollateral,
+    pub liable: bool, // True once the guaranteed loan has been marked defaulted, until written off
+    pub paid_amount: i128,
 }
 
 #[derive(Clone)]
@@ -44,6 +139,164 @@ pub enum TransactionType {
     LoanApproval,
     Repayment,
     Default,
+    Refinance,
+    Restructure,
+    GuarantorRepayment,
+    Recovery,
+    WriteOff,
+    LineDraw,
+    LineRepayment,
+    Advance,
+    AdvanceRepayment,
+    OriginationFee,
+    ProtocolFee,
+    FeeClaim,
+    Acceleration,
+    PositionClaim,
+}
+
+// Share of a loan's lender proceeds held by a funder
+#[derive(Clone)]
+#[contracttype]
+pub struct LoanPosition {
+    pub owner: Address,
+    pub share_bps: u32, // Shares of a loan sum to 10,000
+}
+
+// Revolving credit line backed by a salary stream
+#[derive(Clone)]
+#[contracttype]
+pub struct CreditLine {
+    pub id: u32, // Shares the ID space with loans
+    pub borrower: Address,
+    pub limit: i128,
+    pub drawn: i128,            // Outstanding principal
+    pub accrued_interest: i128, // Unpaid interest accrued so far
+    pub interest_paid: i128,
+    pub risk_tier: u32,
+    pub interest_rate: u32, // Basis points (e.g., 500 = 5%)
+    pub collateral_stream_id: u32,
+    pub employer: Address,      // Employer paying the collateral stream
+    pub opened_at: u64,
+    pub last_accrual: u64,
+    pub is_open: bool,
+    pub is_approved: bool,      // Draws are allowed once an admin approves the line
+}
+
+// Earned wage advance against already-accrued stream balance
+#[derive(Clone)]
+#[contracttype]
+pub struct Advance {
+    pub id: u32, // Shares the ID space with loans
+    pub borrower: Address,
+    pub stream_id: u32,
+    pub employer: Address, // Employer paying the stream
+    pub amount: i128,
+    pub fee: i128, // Flat fee, repaid after the advanced amount
+    pub repaid_amount: i128,
+    pub created_at: u64,
+    pub is_repaid: bool,
+}
+
+// Loan offering defined by the admin
+#[derive(Clone)]
+#[contracttype]
+pub struct LoanProduct {
+    pub id: u32,
+    pub name: String,
+    pub min_amount: i128,
+    pub max_amount: i128,
+    pub term_options: Vec<u32>,           // Terms in months a borrower can choose from
+    pub rate_spreads: Vec<u32>,           // Basis points added to the tier rate, one per tier 1-5
+    pub origination_fee_bps: Option<u32>, // Overrides the protocol origination fee when set
+    pub eligible_tiers: Vec<u32>,
+    pub is_active: bool,
+}
+
+// Product and term chosen by a borrower when requesting a loan
+#[derive(Clone)]
+#[contracttype]
+pub struct LoanTerms {
+    pub product_id: u32,
+    pub term_months: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeConfig {
+    pub origination_fee_bps: u32, // Charged on principal at disbursement
+    pub protocol_fee_bps: u32,    // Protocol cut of interest paid
+    pub fee_recipient: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CreditLimit {
+    pub risk_tier: u32,
+    pub pledged_value: i128, // Remaining value of streams pledged on open loans
+    pub limit: i128,
+    pub used: i128,          // Outstanding approved balances plus pending requests
+    pub available: i128,
+}
+
+// Running aggregates for a risk tier or origination cohort, updated on each loan state transition
+#[derive(Clone)]
+#[contracttype]
+pub struct PortfolioStats {
+    pub loans: u32,
+    pub principal: i128,
+    pub outstanding: i128,
+    pub repaid: i128, // Principal repaid, including recoveries after default
+    pub defaulted: u32,
+    pub rate_weighted_principal: i128, // Sum of principal * interest rate
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PortfolioBreakdown {
+    pub key: u32, // Risk tier, or origination month as YYYYMM
+    pub loans: u32,
+    pub principal: i128,
+    pub outstanding: i128,
+    pub repaid: i128,
+    pub defaulted: u32,
+    pub default_rate: u32,          // Basis points of loans defaulted
+    pub weighted_average_rate: u32, // Basis points, weighted by principal
+}
+
+// Balance movements of a loan over a statement period
+// Balance is principal plus unpaid interest:
+// closing = opening + disbursed + fees + interest_accrued - payments - adjustments
+#[derive(Clone)]
+#[contracttype]
+pub struct LoanStatement {
+    pub loan_id: u32,
+    pub from_ts: u64,
+    pub to_ts: u64,
+    pub opening_balance: i128,
+    pub disbursed: i128,       // Principal paid out, net of origination fees
+    pub fees: i128,            // Origination fees added to the balance
+    pub interest_accrued: i128,
+    pub payments: i128,        // Borrower, guarantor and collateral payments
+    pub adjustments: i128,     // Balances written off or moved out by refinancing, negative when moved in
+    pub closing_balance: i128,
+}
+
+// Balance movements across all loans of a borrower over a statement period
+#[derive(Clone)]
+#[contracttype]
+pub struct BorrowerStatement {
+    pub borrower: Address,
+    pub from_ts: u64,
+    pub to_ts: u64,
+    pub opening_balance: i128,
+    pub disbursed: i128,
+    pub fees: i128,
+    pub interest_accrued: i128,
+    pub payments: i128,
+    pub adjustments: i128,
+    pub closing_balance: i128,
+    pub loans: Vec<LoanStatement>,
 }
 
 #[derive(Clone)]
@@ -55,12 +308,19 @@ pub struct LoanSummary {
     pub approved_loans: u32,
     pub repaid_loans: u32,
     pub defaulted_loans: u32,
+    pub refinanced_loans: u32,
+    pub written_off_loans: u32,
+    pub at_risk_loans: u32,
     pub total_outstanding: i128,
+    pub total_recovered: i128,
+    pub total_written_off: i128,
+    pub reserve_balance: i128,
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
+    Admin,
     LoanCounter,
     TransactionCounter,
     Loan(u32),
@@ -69,15 +329,53 @@ pub enum DataKey {
     RiskMultiplier(u32),
     AllLoanIds,
     AllTransactionIds,
+    Guarantee(u32),
+    GuarantorLoans(Address),
+    StreamingContract,
+    WorkProfileContract,
+    RequireVerifiedProfile,
+    ReserveRate,
+    ReserveBalance,
+    TotalWrittenOff,
+    FeeConfig,
+    FeeBalance,
+    BorrowerRiskTier(Address),
+    CreditLine(u32),
+    BorrowerCreditLines(Address),
+    AdvanceFeeRate,
+    Advance(u32),
+    BorrowerAdvances(Address),
+    StreamAdvances(u32),
+    LoanPositions(u32),
+    PositionProceeds(Address),
+    TierStats(u32),
+    CohortStats(u32),
+    Cohorts,
+    MaxEmployerExposure,
+    EmployerExposure(Address),
+    AccelerationRate,
+    BorrowerAtRisk(Address),
+    LoanProduct(u32),
+    LoanProductCounter,
+    OnTimeReported(u32), // Last installment period reported on time for a loan
 }
 
+const SECONDS_PER_MONTH: u64 = 30 * 24 * 60 * 60;
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
 #[contract]
 pub struct LendingContract;
 
 #[contractimpl]
 impl LendingContract {
-    /// Initialize contract with risk tier multipliers
-    pub fn initialize(env: Env) {
+    /// Initialize contract with the admin and risk tier multipliers
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().persistent().has(&DataKey::Admin) {
+            panic!("Already initialized");
+        }
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        
         // Risk tier interest rates (basis points above base rate)
         env.storage().persistent().set(&DataKey::RiskMultiplier(1), &400u32); // Tier 1: 4% APR
         env.storage().persistent().set(&DataKey::RiskMultiplier(2), &450u32); // Tier 2: 4.5% APR
@@ -91,7 +389,216 @@ impl LendingContract {
         env.storage().persistent().set(&DataKey::AllTransactionIds, &empty_vec);
     }
     
+    /// Sets the salary streaming contract used to recover defaulted loans (admin function)
+    pub fn set_streaming_contract(env: Env, admin: Address, streaming_contract: Address) {
+        Self::require_admin(&env, &admin);
+        
+        env.storage().persistent().set(&DataKey::StreamingContract, &streaming_contract);
+    }
+    
+    /// Sets the share of interest payments that funds the loss reserve (admin function)
+    pub fn set_reserve_rate(env: Env, admin: Address, reserve_bps: u32) {
+        Self::require_admin(&env, &admin);
+        
+        let protocol_fee_bps = Self::get_fee_config(env.clone()).map_or(0, |config| config.protocol_fee_bps);
+        if reserve_bps + protocol_fee_bps > 10_000 {
+            panic!("Reserve and protocol shares cannot exceed 100% of interest");
+        }
+        
+        env.storage().persistent().set(&DataKey::ReserveRate, &reserve_bps);
+    }
+    
+    /// Gets the loss reserve balance
+    pub fn get_reserve_balance(env: Env) -> i128 {
+        env.storage().persistent().get(&DataKey::ReserveBalance).unwrap_or(0)
+    }
+    
+    /// Sets origination and protocol interest fees and the address that can claim them (admin function)
+    pub fn set_fee_config(
+        env: Env,
+        admin: Address,
+        origination_fee_bps: u32,
+        protocol_fee_bps: u32,
+        fee_recipient: Address,
+    ) {
+        Self::require_admin(&env, &admin);
+        
+        let reserve_bps: u32 = env.storage().persistent().get(&DataKey::ReserveRate).unwrap_or(0);
+        if origination_fee_bps > 10_000 {
+            panic!("Origination fee cannot exceed 100%");
+        }
+        if reserve_bps + protocol_fee_bps > 10_000 {
+            panic!("Reserve and protocol shares cannot exceed 100% of interest");
+        }
+        
+        let config = FeeConfig {
+            origination_fee_bps,
+            protocol_fee_bps,
+            fee_recipient,
+        };
+        env.storage().persistent().set(&DataKey::FeeConfig, &config);
+    }
+    
+    /// Gets the fee configuration, if set
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        env.storage().persistent().get(&DataKey::FeeConfig)
+    }
+    
+    /// Gets protocol fees collected and not yet claimed
+    pub fn get_fee_balance(env: Env) -> i128 {
+        env.storage().persistent().get(&DataKey::FeeBalance).unwrap_or(0)
+    }
+    
+    /// Claims all collected protocol fees (fee recipient function)
+    /// Returns the amount claimed.
+    pub fn claim_fees(env: Env, recipient: Address) -> i128 {
+        recipient.require_auth();
+        
+        let config = Self::get_fee_config(env.clone()).expect("Fees not configured");
+        if config.fee_recipient != recipient {
+            panic!("Unauthorized");
+        }
+        
+        let amount = Self::get_fee_balance(env.clone());
+        env.storage().persistent().set(&DataKey::FeeBalance, &0i128);
+        
+        // Record transaction (not tied to a loan)
+        Self::record_transaction(&env, 0, TransactionType::FeeClaim, amount, recipient);
+        
+        amount
+    }
+    
+    /// Sets the flat fee charged on wage advances (admin function)
+    pub fn set_advance_fee(env: Env, admin: Address, advance_fee_bps: u32) {
+        Self::require_admin(&env, &admin);
+        
+        if advance_fee_bps > 10_000 {
+            panic!("Advance fee cannot exceed 100%");
+        }
+        
+        env.storage().persistent().set(&DataKey::AdvanceFeeRate, &advance_fee_bps);
+    }
+    
+    /// Adds a loan product to the catalog (admin function)
+    /// The product ID is assigned by the contract. Returns the new product ID.
+    pub fn create_loan_product(env: Env, admin: Address, product: LoanProduct) -> u32 {
+        Self::require_admin(&env, &admin);
+        
+        if product.min_amount <= 0 || product.max_amount < product.min_amount {
+            panic!("Invalid amount range");
+        }
+        if product.term_options.is_empty() || product.term_options.contains(0) {
+            panic!("Invalid term options");
+        }
+        if product.rate_spreads.len() != 5 {
+            panic!("Rate spreads must cover tiers 1-5");
+        }
+        if product.origination_fee_bps.is_some_and(|fee_bps| fee_bps > 10_000) {
+            panic!("Origination fee cannot exceed 100%");
+        }
+        if product.eligible_tiers.is_empty() || product.eligible_tiers.iter().any(|tier| !(1..=5).contains(&tier)) {
+            panic!("Invalid eligible tiers");
+        }
+        
+        let product_id: u32 = env.storage().persistent().get(&DataKey::LoanProductCounter).unwrap_or(0) + 1;
+        let product = LoanProduct {
+            id: product_id,
+            is_active: true,
+            ..product
+        };
+        env.storage().persistent().set(&DataKey::LoanProduct(product_id), &product);
+        env.storage().persistent().set(&DataKey::LoanProductCounter, &product_id);
+        
+        product_id
+    }
+    
+    /// Activates or retires a loan product (admin function)
+    /// Retired products accept no new requests; existing loans are unaffected.
+    pub fn set_loan_product_active(env: Env, admin: Address, product_id: u32, is_active: bool) {
+        Self::require_admin(&env, &admin);
+        
+        let mut product = Self::get_loan_product(env.clone(), product_id);
+        product.is_active = is_active;
+        env.storage().persistent().set(&DataKey::LoanProduct(product_id), &product);
+    }
+    
+    /// Gets a loan product by ID
+    pub fn get_loan_product(env: Env, product_id: u32) -> LoanProduct {
+        env.storage().persistent()
+            .get(&DataKey::LoanProduct(product_id))
+            .expect("Loan product not found")
+    }
+    
+    /// Gets all active loan products
+    pub fn get_loan_products(env: Env) -> Vec<LoanProduct> {
+        let counter: u32 = env.storage().persistent().get(&DataKey::LoanProductCounter).unwrap_or(0);
+        let mut products = Vec::new(&env);
+        
+        for product_id in 1..=counter {
+            let product = Self::get_loan_product(env.clone(), product_id);
+            if product.is_active {
+                products.push_back(product);
+            }
+        }
+        
+        products
+    }
+    
+    /// Sets the share of the remaining balance that becomes due when a loan is moved at risk (admin function)
+    pub fn set_acceleration_rate(env: Env, admin: Address, acceleration_bps: u32) {
+        Self::require_admin(&env, &admin);
+        
+        if acceleration_bps > 10_000 {
+            panic!("Acceleration cannot exceed 100%");
+        }
+        
+        env.storage().persistent().set(&DataKey::AccelerationRate, &acceleration_bps);
+    }
+    
+    /// Sets the maximum outstanding principal allowed across loans collateralized by one employer's streams (admin function)
+    pub fn set_max_employer_exposure(env: Env, admin: Address, max_exposure: i128) {
+        Self::require_admin(&env, &admin);
+        
+        if max_exposure <= 0 {
+            panic!("Max exposure must be positive");
+        }
+        
+        env.storage().persistent().set(&DataKey::MaxEmployerExposure, &max_exposure);
+    }
+    
+    /// Gets outstanding principal of approved loans collateralized by an employer's streams
+    pub fn get_employer_exposure(env: Env, employer: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::EmployerExposure(employer)).unwrap_or(0)
+    }
+    
+    /// Gets the salary streaming contract, if configured
+    pub fn get_streaming_contract(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::StreamingContract)
+    }
+    
+    /// Sets the work profile contract that receives borrowers' repayment events (admin function)
+    pub fn set_work_profile_contract(env: Env, admin: Address, work_profile_contract: Address) {
+        Self::require_admin(&env, &admin);
+        
+        env.storage().persistent().set(&DataKey::WorkProfileContract, &work_profile_contract);
+    }
+    
+    /// Gets the work profile contract, if configured
+    pub fn get_work_profile_contract(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::WorkProfileContract)
+    }
+    
+    /// Sets whether new loans require an attested work profile (admin function)
+    pub fn set_require_verified_profile(env: Env, admin: Address, required: bool) {
+        Self::require_admin(&env, &admin);
+        
+        env.storage().persistent().set(&DataKey::RequireVerifiedProfile, &required);
+    }
+    
     /// Requests a loan using salary stream as collateral
+    /// The risk tier comes from the work profile contract when configured, ignoring `risk_tier`; disputed profiles are refused.
+    /// An optional guarantor pledges their own stream as secondary collateral; it doesn't raise the credit limit.
+    /// The salary streaming contract must be configured: pledged streams back the credit limit.
     /// Returns loan ID for tracking. Loan will be in Pending status until approved.
     pub fn request_loan(
         env: Env,
@@ -99,34 +606,80 @@ impl LendingContract {
         amount: i128,
         risk_tier: u32,
         collateral_stream_id: u32,
+        guarantor: Option<GuarantorPledge>,
+        terms: LoanTerms,
     ) -> u32 {
         // Require authorization from borrower
         borrower.require_auth();
+        Self::require_not_at_risk(&env, &borrower);
         
-        // Validate risk tier
-        if risk_tier < 1 || risk_tier > 5 {
-            panic!("Invalid risk tier");
+        // Credit limit and employer exposure are read from the collateral stream
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        
+        // Only attested work profiles are accepted when required
+        let require_verified: bool = env.storage().persistent().get(&DataKey::RequireVerifiedProfile).unwrap_or(false);
+        if require_verified {
+            let work_profile_contract = Self::get_work_profile_contract(env.clone())
+                .expect("Work profile contract not configured");
+            if !WorkProfileClient::new(&env, &work_profile_contract).is_profile_verified(&borrower) {
+                panic!("Work profile is not verified");
+            }
+        }
+        
+        // Guarantor must also authorize
+        if let Some(pledge) = &guarantor {
+            pledge.guarantor.require_auth();
+            
+            if pledge.guarantor == borrower {
+                panic!("Borrower cannot guarantee own loan");
+            }
+            match pledge.collateral {
+                GuarantorCollateral::Stream(stream_id) => {
+                    Self::get_pledged_stream(&env, &streaming_contract, stream_id, &pledge.guarantor);
+                },
+            }
         }
         
+        let collateral_stream = Self::get_pledged_stream(&env, &streaming_contract, collateral_stream_id, &borrower);
+        let risk_tier = Self::assess_risk_tier(&env, &borrower, risk_tier);
+        
         // Validate loan amount (must be positive)
         if amount <= 0 {
             panic!("Loan amount must be positive");
         }
         
-        // Check if borrower has any outstanding loans
-        let outstanding = Self::get_outstanding_loans(env.clone(), borrower.clone());
-        if outstanding > 0 {
-            panic!("Borrower has outstanding loans");
+        // Validate the request against the chosen product
+        let product = Self::get_loan_product(env.clone(), terms.product_id);
+        if !product.is_active {
+            panic!("Loan product is not active");
+        }
+        if amount < product.min_amount || amount > product.max_amount {
+            panic!("Amount outside product limits");
+        }
+        if !product.term_options.contains(terms.term_months) {
+            panic!("Term not offered by product");
+        }
+        if !product.eligible_tiers.contains(risk_tier) {
+            panic!("Risk tier not eligible for product");
         }
         
-        // Get interest rate for risk tier
-        let interest_rate: u32 = env.storage().persistent()
-            .get(&DataKey::RiskMultiplier(risk_tier))
-            .unwrap_or(600u32);
+        // Check aggregate exposure against the borrower's credit limit
+        env.storage().persistent().set(&DataKey::BorrowerRiskTier(borrower.clone()), &risk_tier);
+        let credit = Self::calculate_credit_limit(&env, &borrower, Some(collateral_stream_id));
+        if amount > credit.available {
+            panic!("Credit limit exceeded");
+        }
+        
+        // Check the employer behind the collateral stream can take more exposure
+        let employer = collateral_stream.employer;
+        Self::check_employer_exposure(&env, &employer, amount);
+        
+        // Get interest rate for risk tier plus the product spread
+        let interest_rate = Self::product_rate(&env, &product, risk_tier);
         
         // Get next loan ID
-        let mut counter: u32 = env.storage().persistent().get(&DataKey::LoanCounter).unwrap_or(0);
-        counter += 1;
+        let counter = Self::next_loan_id(&env);
         
         // Create loan in PENDING status - requires admin approval
         let loan = Loan {
@@ -139,25 +692,36 @@ impl LendingContract {
             created_at: env.ledger().timestamp(),
             repaid_amount: 0,
             collateral_stream_id,
+            term_months: terms.term_months,
+            installment_amount: Self::calculate_installment(amount, terms.term_months),
+            scheduled_installment: None,
+            previous_loan_id: None,
+            guarantor: guarantor.as_ref().map(|pledge| pledge.guarantor.clone()),
+            recovered_amount: 0,
+            accrued_interest: 0,
+            interest_paid: 0,
+            last_accrual: env.ledger().timestamp(),
+            written_off_amount: 0,
+            origination_fee: 0,
+            approved_at: 0,
+            employer,
+            product_id: terms.product_id,
         };
         
-        // Store loan
-        env.storage().persistent().set(&DataKey::Loan(counter), &loan);
-        env.storage().persistent().set(&DataKey::LoanCounter, &counter);
-        
-        // Add to borrower's loan list
-        let mut borrower_loans: Vec<u32> = env.storage().persistent()
-            .get(&DataKey::BorrowerLoans(borrower.clone()))
-            .unwrap_or_else(|| Vec::new(&env));
-        borrower_loans.push_back(counter);
-        env.storage().persistent().set(&DataKey::BorrowerLoans(borrower.clone()), &borrower_loans);
+        // Store loan and index it for borrower and global lookups
+        Self::store_new_loan(&env, &loan);
         
-        // Add to all loans list
-        let mut all_loans: Vec<u32> = env.storage().persistent()
-            .get(&DataKey::AllLoanIds)
-            .unwrap_or_else(|| Vec::new(&env));
-        all_loans.push_back(counter);
-        env.storage().persistent().set(&DataKey::AllLoanIds, &all_loans);
+        // Store guarantee and add to guarantor's list
+        if let Some(pledge) = guarantor {
+            let guarantee = Guarantee {
+                loan_id: counter,
+                guarantor: pledge.guarantor,
+                collateral: pledge.collateral,
+                liable: false,
+                paid_amount: 0,
+            };
+            Self::store_guarantee(&env, &guarantee);
+        }
         
         // Record transaction
         Self::record_transaction(&env, counter, TransactionType::LoanRequest, amount, borrower);
@@ -168,8 +732,7 @@ impl LendingContract {
     /// Approves a pending loan (admin function)
     /// Validates loan details before approval
     pub fn approve_loan(env: Env, admin: Address, loan_id: u32) {
-        // In a real implementation, you'd check admin permissions
-        admin.require_auth();
+        Self::require_admin(&env, &admin);
         
         let mut loan: Loan = env.storage().persistent()
             .get(&DataKey::Loan(loan_id))
@@ -179,24 +742,55 @@ impl LendingContract {
         if !matches!(loan.status, LoanStatus::Pending) {
             panic!("Loan is not pending");
         }
+        Self::require_not_at_risk(&env, &loan.borrower);
         
-        // Additional validation: Check if borrower still has no outstanding loans
-        let outstanding = Self::get_outstanding_loans(env.clone(), loan.borrower.clone());
-        if outstanding > 0 {
-            panic!("Borrower has outstanding loans, cannot approve new loan");
+        // Additional validation: Check approved balances and open line limits still fit under the credit limit
+        // Usage counts this request; other pending requests aren't committed yet and are left out.
+        let credit = Self::calculate_credit_limit(&env, &loan.borrower, None);
+        let mut committed = credit.used;
+        for other_id in Self::get_borrower_loans(env.clone(), loan.borrower.clone()).iter() {
+            let other = Self::get_loan(env.clone(), other_id);
+            if other_id != loan_id && matches!(other.status, LoanStatus::Pending) {
+                committed -= other.amount;
+            }
         }
+        if committed > credit.limit {
+            panic!("Credit limit exceeded, cannot approve new loan");
+        }
+        Self::check_employer_exposure(&env, &loan.employer, loan.amount);
+        
+        // Origination fee is deducted from the disbursed amount
+        // Product fee schedule takes precedence over the protocol fee
+        let origination_fee_bps = Self::get_loan_product(env.clone(), loan.product_id)
+            .origination_fee_bps
+            .unwrap_or_else(|| Self::get_fee_config(env.clone()).map_or(0, |config| config.origination_fee_bps));
+        let origination_fee = loan.amount * origination_fee_bps as i128 / 10_000;
         
-        // Approve the loan
+        // Approve the loan, interest accrues from approval
         loan.status = LoanStatus::Approved;
+        loan.last_accrual = env.ledger().timestamp();
+        loan.origination_fee = origination_fee;
+        loan.approved_at = env.ledger().timestamp();
         env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        Self::record_origination(&env, &loan);
+        
+        // Approving admin funds the loan and holds the whole position
+        let mut positions = Vec::new(&env);
+        positions.push_back(LoanPosition { owner: admin, share_bps: 10_000 });
+        env.storage().persistent().set(&DataKey::LoanPositions(loan_id), &positions);
         
         // Record transaction
-        Self::record_transaction(&env, loan_id, TransactionType::LoanApproval, loan.amount, loan.borrower);
+        Self::record_transaction(&env, loan_id, TransactionType::LoanApproval, loan.amount, loan.borrower.clone());
+        
+        if origination_fee > 0 {
+            Self::add_fee(&env, origination_fee);
+            Self::record_transaction(&env, loan_id, TransactionType::OriginationFee, origination_fee, loan.borrower);
+        }
     }
     
     /// Rejects a pending loan (admin function)
     pub fn reject_loan(env: Env, admin: Address, loan_id: u32) {
-        admin.require_auth();
+        Self::require_admin(&env, &admin);
         
         let mut loan: Loan = env.storage().persistent()
             .get(&DataKey::Loan(loan_id))
@@ -246,82 +840,909 @@ impl LendingContract {
             panic!("Unauthorized");
         }
         
-        // Only repay approved loans
-        if !matches!(loan.status, LoanStatus::Approved) {
+        // Only repay approved loans, including those accelerated at risk
+        if !matches!(loan.status, LoanStatus::Approved | LoanStatus::AtRisk) {
             panic!("Loan is not approved");
         }
         
-        // Calculate remaining amount
-        let remaining = loan.amount - loan.repaid_amount;
+        if amount <= 0 {
+            panic!("Repayment amount must be positive");
+        }
+        
+        Self::accrue_interest(&env, &mut loan);
+        let was_behind = loan.repaid_amount < Self::principal_due(&env, &loan);
+        
+        // Calculate remaining amount including unpaid interest
+        let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
         let payment = if amount > remaining { remaining } else { amount };
         
-        // Update repaid amount
-        loan.repaid_amount += payment;
+        // Interest is paid first, then principal
+        let interest_payment = payment.min(loan.accrued_interest);
+        loan.accrued_interest -= interest_payment;
+        loan.interest_paid += interest_payment;
+        loan.repaid_amount += payment - interest_payment;
+        Self::update_portfolio(&env, &loan, |stats| {
+            stats.outstanding -= payment - interest_payment;
+            stats.repaid += payment - interest_payment;
+        });
+        Self::adjust_employer_exposure(&env, &loan.employer, -(payment - interest_payment));
         
         // Check if fully repaid
-        if loan.repaid_amount >= loan.amount {
+        if loan.repaid_amount >= loan.amount && loan.accrued_interest == 0 {
             loan.status = LoanStatus::Repaid;
         }
         
         env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
         
+        // Report repayment behaviour to the borrower's credit history
+        // On-time payments count once per installment period, and only for a full installment.
+        if matches!(loan.status, LoanStatus::Repaid) {
+            Self::report_credit_event(&env, &borrower, CreditEvent::Payoff);
+        } else if was_behind {
+            Self::report_credit_event(&env, &borrower, CreditEvent::LatePayment);
+        } else if payment >= loan.installment_amount {
+            let period = env.ledger().timestamp().saturating_sub(loan.approved_at) / SECONDS_PER_MONTH;
+            let reported: Option<u64> = env.storage().persistent().get(&DataKey::OnTimeReported(loan_id));
+            if reported != Some(period) {
+                env.storage().persistent().set(&DataKey::OnTimeReported(loan_id), &period);
+                Self::report_credit_event(&env, &borrower, CreditEvent::OnTimePayment);
+            }
+        }
+        
         // Record transaction
-        Self::record_transaction(&env, loan_id, TransactionType::Repayment, payment, borrower);
+        Self::record_transaction(&env, loan_id, TransactionType::Repayment, payment, borrower.clone());
+        
+        // Lenders receive the payment net of reserve and protocol shares
+        let retained = Self::distribute_interest(&env, loan_id, interest_payment, borrower);
+        Self::route_to_positions(&env, loan_id, payment - retained);
         
         payment
     }
     
-    /// Marks a loan as defaulted (admin function)
-    pub fn mark_default(env: Env, admin: Address, loan_id: u32) {
-        admin.require_auth();
+    /// Refinances an approved loan at the rate of the borrower's current risk tier (admin function)
+    /// The tier comes from the work profile contract when configured, as for new requests;
+    /// `risk_tier` is only used when no work profile contract is configured.
+    /// Closes the old loan as Refinanced and opens a new approved loan for the outstanding balance.
+    /// Returns the new loan ID.
+    pub fn refinance_loan(env: Env, admin: Address, loan_id: u32, risk_tier: u32) -> u32 {
+        Self::require_admin(&env, &admin);
         
         let mut loan: Loan = env.storage().persistent()
             .get(&DataKey::Loan(loan_id))
             .expect("Loan not found");
         
-        // Only default approved loans
+        // Only refinance approved loans
         if !matches!(loan.status, LoanStatus::Approved) {
             panic!("Loan is not approved");
         }
         
-        loan.status = LoanStatus::Defaulted;
-        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        let risk_tier = Self::assess_risk_tier(&env, &loan.borrower, risk_tier);
         
-        // Record transaction
-        Self::record_transaction(&env, loan_id, TransactionType::Default, 0, loan.borrower);
-    }
-    
-    /// Gets outstanding loans for a borrower
-    pub fn get_outstanding_loans(env: Env, borrower: Address) -> i128 {
-        let loan_ids: Vec<u32> = env.storage().persistent()
-            .get(&DataKey::BorrowerLoans(borrower))
-            .unwrap_or_else(|| Vec::new(&env));
+        // Unpaid interest is capitalized into the new loan
+        Self::accrue_interest(&env, &mut loan);
+        let outstanding = loan.amount - loan.repaid_amount + loan.accrued_interest;
         
-        let mut total_outstanding = 0i128;
+        // Close the old loan
+        loan.status = LoanStatus::Refinanced;
+        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        // The rolled-over principal moves to the new loan, so it leaves the old loan's aggregates
+        let rolled_over = loan.amount - loan.repaid_amount;
+        Self::update_portfolio(&env, &loan, |stats| {
+            stats.principal -= rolled_over;
+            stats.outstanding -= rolled_over;
+            stats.rate_weighted_principal -= rolled_over * loan.interest_rate as i128;
+        });
+        Self::adjust_employer_exposure(&env, &loan.employer, -rolled_over);
+        Self::record_transaction(&env, loan_id, TransactionType::Refinance, outstanding, loan.borrower.clone());
         
-        for loan_id in loan_ids.iter() {
-            let loan: Loan = env.storage().persistent()
-                .get(&DataKey::Loan(loan_id))
-                .unwrap();
-            
-            if matches!(loan.status, LoanStatus::Approved) {
-                total_outstanding += loan.amount - loan.repaid_amount;
-            }
+        // Open the replacement loan at the current tier rate of the same product, already approved
+        let product = Self::get_loan_product(env.clone(), loan.product_id);
+        let interest_rate = Self::product_rate(&env, &product, risk_tier);
+        let new_loan_id = Self::next_loan_id(&env);
+        let new_loan = Loan {
+            id: new_loan_id,
+            borrower: loan.borrower.clone(),
+            amount: outstanding,
+            status: LoanStatus::Approved,
+            risk_tier,
+            interest_rate,
+            created_at: env.ledger().timestamp(),
+            repaid_amount: 0,
+            collateral_stream_id: loan.collateral_stream_id,
+            term_months: loan.term_months,
+            installment_amount: Self::calculate_installment(outstanding, loan.term_months),
+            scheduled_installment: None,
+            previous_loan_id: Some(loan_id),
+            guarantor: loan.guarantor.clone(),
+            recovered_amount: 0,
+            accrued_interest: 0,
+            interest_paid: 0,
+            last_accrual: env.ledger().timestamp(),
+            written_off_amount: 0,
+            origination_fee: 0,
+            approved_at: env.ledger().timestamp(),
+            employer: loan.employer.clone(),
+            product_id: loan.product_id,
+        };
+        Self::store_new_loan(&env, &new_loan);
+        Self::record_origination(&env, &new_loan);
+        
+        // Guarantee and lender positions carry over to the replacement loan
+        if let Some(mut guarantee) = Self::get_guarantee(env.clone(), loan_id) {
+            guarantee.loan_id = new_loan_id;
+            Self::store_guarantee(&env, &guarantee);
         }
+        let positions = Self::get_position_owner(env.clone(), loan_id);
+        env.storage().persistent().set(&DataKey::LoanPositions(new_loan_id), &positions);
+        Self::record_transaction(&env, new_loan_id, TransactionType::LoanApproval, outstanding, loan.borrower);
         
-        total_outstanding
+        new_loan_id
     }
     
-    /// Gets loan details
-    pub fn get_loan(env: Env, loan_id: u32) -> Loan {
-        env.storage().persistent()
+    /// Restructures an approved loan by extending its term (admin function)
+    /// The outstanding balance is spread over the months left in the new term,
+    /// which lowers the installment. Returns the new installment amount.
+    pub fn restructure_loan(env: Env, admin: Address, loan_id: u32, new_term_months: u32) -> i128 {
+        Self::require_admin(&env, &admin);
+        
+        let mut loan: Loan = env.storage().persistent()
             .get(&DataKey::Loan(loan_id))
-            .expect("Loan not found")
-    }
-    
-    /// Gets all loans for a borrower
-    pub fn get_borrower_loans(env: Env, borrower: Address) -> Vec<u32> {
-        env.storage().persistent()
+            .expect("Loan not found");
+        
+        // Only restructure approved loans
+        if !matches!(loan.status, LoanStatus::Approved) {
+            panic!("Loan is not approved");
+        }
+        
+        if new_term_months <= loan.term_months {
+            panic!("New term must be longer than current term");
+        }
+        
+        // Months already elapsed since approval count against the new term
+        let elapsed_months = (env.ledger().timestamp().saturating_sub(loan.approved_at) / SECONDS_PER_MONTH) as u32;
+        if new_term_months <= elapsed_months {
+            panic!("New term has already elapsed");
+        }
+        
+        Self::accrue_interest(&env, &mut loan);
+        let outstanding = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        loan.term_months = new_term_months;
+        loan.installment_amount = Self::calculate_installment(outstanding, new_term_months - elapsed_months);
+        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        
+        // Record transaction with the new installment amount
+        Self::record_transaction(&env, loan_id, TransactionType::Restructure, loan.installment_amount, loan.borrower);
+        
+        loan.installment_amount
+    }
+    
+    /// Gets the refinancing chain ending at a loan, oldest loan first
+    pub fn get_loan_history(env: Env, loan_id: u32) -> Vec<Loan> {
+        let mut history = Vec::new(&env);
+        let mut next_id = Some(loan_id);
+        
+        while let Some(id) = next_id {
+            let loan = Self::get_loan(env.clone(), id);
+            next_id = loan.previous_loan_id;
+            history.push_front(loan);
+        }
+        
+        history
+    }
+    
+    /// Marks a loan as defaulted (admin function)
+    pub fn mark_default(env: Env, admin: Address, loan_id: u32) {
+        Self::require_admin(&env, &admin);
+        
+        let mut loan: Loan = env.storage().persistent()
+            .get(&DataKey::Loan(loan_id))
+            .expect("Loan not found");
+        
+        // Only default approved loans, including those at risk
+        if !matches!(loan.status, LoanStatus::Approved | LoanStatus::AtRisk) {
+            panic!("Loan is not approved");
+        }
+        
+        // Interest stops accruing at default
+        Self::accrue_interest(&env, &mut loan);
+        loan.status = LoanStatus::Defaulted;
+        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        Self::update_portfolio(&env, &loan, |stats| {
+            stats.outstanding -= loan.amount - loan.repaid_amount;
+            stats.defaulted += 1;
+        });
+        Self::adjust_employer_exposure(&env, &loan.employer, -(loan.amount - loan.repaid_amount));
+        
+        // Guarantor becomes liable for the remaining balance
+        let guarantee = Self::get_guarantee(env.clone(), loan_id).map(|mut guarantee| {
+            guarantee.liable = true;
+            env.storage().persistent().set(&DataKey::Guarantee(loan_id), &guarantee);
+            guarantee
+        });
+        
+        // Redirect future accruals of the collateral stream, and of a guarantor's pledged stream,
+        // until principal and unpaid interest are recovered
+        let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        if remaining > 0 {
+            if let Some(streaming_contract) = Self::get_streaming_contract(env.clone()) {
+                let streaming = SalaryStreamingClient::new(&env, &streaming_contract);
+                let lender = env.current_contract_address();
+                streaming.place_lien(&lender, &loan.collateral_stream_id, &remaining);
+                if let Some(GuarantorCollateral::Stream(stream_id)) = guarantee.map(|guarantee| guarantee.collateral) {
+                    streaming.place_lien(&lender, &stream_id, &remaining);
+                }
+            }
+        }
+        
+        Self::report_credit_event(&env, &loan.borrower, CreditEvent::Default);
+        
+        // Record transaction
+        Self::record_transaction(&env, loan_id, TransactionType::Default, 0, loan.borrower);
+    }
+    
+    /// Collects accruals redirected from a defaulted loan's collateral stream,
+    /// then from the stream a liable guarantor pledged. Anyone may trigger collection.
+    /// Returns the amount recovered.
+    pub fn recover_from_stream(env: Env, loan_id: u32) -> i128 {
+        let loan: Loan = env.storage().persistent()
+            .get(&DataKey::Loan(loan_id))
+            .expect("Loan not found");
+        
+        // Only recover defaulted loans
+        if !matches!(loan.status, LoanStatus::Defaulted) {
+            panic!("Loan is not defaulted");
+        }
+        
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        let streaming = SalaryStreamingClient::new(&env, &streaming_contract);
+        let lender = env.current_contract_address();
+        
+        let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        if remaining <= 0 {
+            panic!("Loan has no outstanding balance");
+        }
+        
+        // Collect no more than the stream's claims, which also cover its advances and other defaulted loans
+        let claims = Self::get_stream_claim(env.clone(), loan.collateral_stream_id, loan.borrower.clone());
+        let mut recovered = streaming.collect_lien(&lender, &loan.collateral_stream_id, &claims);
+        Self::apply_lien_collection(&env, loan.collateral_stream_id, &loan.borrower, recovered);
+        
+        // A guarantor's pledged stream covers what the borrower's stream could not
+        let loan = Self::get_loan(env.clone(), loan_id);
+        let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        if let Some(guarantee) = Self::get_guarantee(env.clone(), loan_id) {
+            if let (true, GuarantorCollateral::Stream(stream_id)) = (guarantee.liable && remaining > 0, guarantee.collateral) {
+                let claims = Self::get_stream_claim(env.clone(), stream_id, guarantee.guarantor.clone());
+                let collected = streaming.collect_lien(&lender, &stream_id, &claims);
+                Self::apply_lien_collection(&env, stream_id, &guarantee.guarantor, collected);
+                recovered += collected;
+            }
+        }
+        
+        // Shrink both liens to what is still owed, releasing the streams once the balance is recovered
+        Self::sync_stream_liens(&env, &Self::get_loan(env.clone(), loan_id));
+        
+        recovered
+    }
+    
+    /// Gets what the lender is still owed from a stream's wages (streaming contract hook)
+    /// Covers unrepaid advances on the stream, defaulted loans it collateralizes and
+    /// defaulted loans it was pledged to guarantee. Lien collection is capped at this amount.
+    pub fn get_stream_claim(env: Env, stream_id: u32, employee: Address) -> i128 {
+        let balance = |loan: &Loan| {
+            if matches!(loan.status, LoanStatus::Defaulted) {
+                loan.amount - loan.repaid_amount + loan.accrued_interest
+            } else {
+                0
+            }
+        };
+        
+        let mut claim = Self::outstanding_advances(&env, stream_id);
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), employee.clone()).iter() {
+            let loan = Self::get_loan(env.clone(), loan_id);
+            if loan.collateral_stream_id == stream_id {
+                claim += balance(&loan);
+            }
+        }
+        
+        for loan_id in Self::get_guarantor_loans(env.clone(), employee).iter() {
+            let guarantee = Self::get_guarantee(env.clone(), loan_id).unwrap();
+            if guarantee.liable && matches!(guarantee.collateral, GuarantorCollateral::Stream(id) if id == stream_id) {
+                claim += balance(&Self::get_loan(env.clone(), loan_id));
+            }
+        }
+        
+        claim
+    }
+    
+    /// Advances wages already earned on a stream, repaid from the stream's next withdrawal
+    /// Bounded by the stream's accrued, unwithdrawn balance. Returns the advance ID.
+    pub fn request_advance(env: Env, borrower: Address, stream_id: u32, amount: i128) -> u32 {
+        // Require authorization from borrower
+        borrower.require_auth();
+        
+        if amount <= 0 {
+            panic!("Advance amount must be positive");
+        }
+        Self::require_not_at_risk(&env, &borrower);
+        
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        let streaming = SalaryStreamingClient::new(&env, &streaming_contract);
+        
+        let stream = streaming.get_stream(&stream_id);
+        if stream.employee != borrower {
+            panic!("Stream does not belong to borrower");
+        }
+        
+        let fee_bps: u32 = env.storage().persistent().get(&DataKey::AdvanceFeeRate).unwrap_or(0);
+        let fee = amount * fee_bps as i128 / 10_000;
+        
+        // Accrued balance must cover this and all unrepaid advances on the stream
+        let available = streaming.calculate_available(&stream_id) - Self::outstanding_advances(&env, stream_id);
+        if amount + fee > available {
+            panic!("Advance exceeds accrued balance");
+        }
+        
+        // Advanced wages count towards the employer's exposure until repaid
+        Self::check_employer_exposure(&env, &stream.employer, amount);
+        Self::adjust_employer_exposure(&env, &stream.employer, amount);
+        
+        // Claim the advanced wages on the stream
+        streaming.place_lien(&env.current_contract_address(), &stream_id, &(amount + fee));
+        
+        let advance_id = Self::next_loan_id(&env);
+        let advance = Advance {
+            id: advance_id,
+            borrower: borrower.clone(),
+            stream_id,
+            employer: stream.employer,
+            amount,
+            fee,
+            repaid_amount: 0,
+            created_at: env.ledger().timestamp(),
+            is_repaid: false,
+        };
+        
+        env.storage().persistent().set(&DataKey::Advance(advance_id), &advance);
+        env.storage().persistent().set(&DataKey::LoanCounter, &advance_id);
+        
+        let mut borrower_advances = Self::get_borrower_advances(env.clone(), borrower.clone());
+        borrower_advances.push_back(advance_id);
+        env.storage().persistent().set(&DataKey::BorrowerAdvances(borrower.clone()), &borrower_advances);
+        
+        let mut stream_advances: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::StreamAdvances(stream_id))
+            .unwrap_or_else(|| Vec::new(&env));
+        stream_advances.push_back(advance_id);
+        env.storage().persistent().set(&DataKey::StreamAdvances(stream_id), &stream_advances);
+        
+        // Record transaction
+        Self::record_transaction(&env, advance_id, TransactionType::Advance, amount, borrower);
+        
+        advance_id
+    }
+    
+    /// Applies wages collected under a lien when the employee withdraws (streaming contract hook)
+    pub fn on_lien_collected(env: Env, stream_id: u32, employee: Address, amount: i128) {
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        streaming_contract.require_auth();
+        
+        Self::apply_lien_collection(&env, stream_id, &employee, amount);
+    }
+    
+    /// Hook called by the salary streaming contract when an employee is deactivated or a stream ends
+    /// Approved loans backed by the ended stream, or by any stream of the employer when the employee
+    /// was deactivated, move to AtRisk with the remaining balance accelerated per the acceleration rate.
+    /// The borrower is blocked from new credit until cleared. Loans backed by other income are untouched.
+    /// Does nothing while no streaming contract is configured, since no loans can be backed by streams.
+    pub fn on_employment_ended(env: Env, employee: Address, employer: Address, stream_id: Option<u32>) {
+        let streaming_contract = match Self::get_streaming_contract(env.clone()) {
+            Some(streaming_contract) => streaming_contract,
+            None => return,
+        };
+        streaming_contract.require_auth();
+        
+        let acceleration_bps: u32 = env.storage().persistent().get(&DataKey::AccelerationRate).unwrap_or(10_000);
+        let mut has_loans = false;
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), employee.clone()).iter() {
+            let mut loan = Self::get_loan(env.clone(), loan_id);
+            let affected = match stream_id {
+                Some(stream_id) => loan.collateral_stream_id == stream_id,
+                None => loan.employer == employer,
+            };
+            if !affected {
+                continue;
+            }
+            if matches!(loan.status, LoanStatus::Pending | LoanStatus::AtRisk) {
+                has_loans = true;
+            }
+            if !matches!(loan.status, LoanStatus::Approved) {
+                continue;
+            }
+            
+            // Accrue up to now so the accelerated amount covers interest earned while approved
+            Self::accrue_interest(&env, &mut loan);
+            loan.status = LoanStatus::AtRisk;
+            loan.scheduled_installment = Some(loan.installment_amount);
+            
+            // Next installment covers the accelerated share of the remaining balance
+            let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
+            let accelerated = remaining * acceleration_bps as i128 / 10_000;
+            if accelerated > loan.installment_amount {
+                loan.installment_amount = accelerated;
+            }
+            env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+            has_loans = true;
+            
+            Self::record_transaction(&env, loan_id, TransactionType::Acceleration, loan.installment_amount, employee.clone());
+        }
+        
+        if has_loans {
+            env.storage().persistent().set(&DataKey::BorrowerAtRisk(employee), &true);
+        }
+    }
+    
+    /// Lifts the at-risk block on a borrower and returns their at-risk loans to approved (admin function)
+    /// Accelerated loans go back to their regular installment schedule.
+    pub fn clear_at_risk(env: Env, admin: Address, borrower: Address) {
+        Self::require_admin(&env, &admin);
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), borrower.clone()).iter() {
+            let mut loan = Self::get_loan(env.clone(), loan_id);
+            if matches!(loan.status, LoanStatus::AtRisk) {
+                loan.status = LoanStatus::Approved;
+                if let Some(installment) = loan.scheduled_installment.take() {
+                    loan.installment_amount = installment;
+                }
+                env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+            }
+        }
+        
+        env.storage().persistent().remove(&DataKey::BorrowerAtRisk(borrower));
+    }
+    
+    /// Checks whether a borrower is blocked from new credit after an employment-ended signal
+    pub fn is_borrower_at_risk(env: Env, borrower: Address) -> bool {
+        env.storage().persistent().get(&DataKey::BorrowerAtRisk(borrower)).unwrap_or(false)
+    }
+    
+    /// Gets advance details
+    pub fn get_advance(env: Env, advance_id: u32) -> Advance {
+        env.storage().persistent()
+            .get(&DataKey::Advance(advance_id))
+            .expect("Advance not found")
+    }
+    
+    /// Gets all advances for a borrower
+    pub fn get_borrower_advances(env: Env, borrower: Address) -> Vec<u32> {
+        env.storage().persistent()
+            .get(&DataKey::BorrowerAdvances(borrower))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+    
+    /// Writes off the unrecovered balance of a defaulted loan as a loss (admin function)
+    /// The loss is absorbed by the reserve as far as its balance allows, and a guarantor is no longer liable.
+    /// Returns the amount written off.
+    pub fn write_off(env: Env, admin: Address, loan_id: u32) -> i128 {
+        Self::require_admin(&env, &admin);
+        
+        let mut loan: Loan = env.storage().persistent()
+            .get(&DataKey::Loan(loan_id))
+            .expect("Loan not found");
+        
+        // Only write off defaulted loans
+        if !matches!(loan.status, LoanStatus::Defaulted) {
+            panic!("Loan is not defaulted");
+        }
+        
+        // Unpaid interest is written off along with the principal
+        let loss = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        
+        loan.status = LoanStatus::WrittenOff;
+        loan.written_off_amount = loss;
+        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        
+        // Release the guarantor with the loss and stop redirecting the collateral streams
+        if let Some(mut guarantee) = Self::get_guarantee(env.clone(), loan_id) {
+            guarantee.liable = false;
+            env.storage().persistent().set(&DataKey::Guarantee(loan_id), &guarantee);
+        }
+        Self::sync_stream_liens(&env, &loan);
+        
+        // Absorb the loss from the reserve
+        let reserve = Self::get_reserve_balance(env.clone());
+        let covered = if loss > reserve { reserve } else { loss };
+        env.storage().persistent().set(&DataKey::ReserveBalance, &(reserve - covered));
+        
+        let total_written_off: i128 = env.storage().persistent().get(&DataKey::TotalWrittenOff).unwrap_or(0);
+        env.storage().persistent().set(&DataKey::TotalWrittenOff, &(total_written_off + loss));
+        
+        // Record transaction
+        Self::record_transaction(&env, loan_id, TransactionType::WriteOff, loss, loan.borrower);
+        
+        loss
+    }
+    
+    /// Repays a defaulted loan on behalf of the borrower (guarantor function)
+    pub fn guarantor_repay(env: Env, guarantor: Address, loan_id: u32, amount: i128) -> i128 {
+        // Require authorization from guarantor
+        guarantor.require_auth();
+        
+        let loan: Loan = env.storage().persistent()
+            .get(&DataKey::Loan(loan_id))
+            .expect("Loan not found");
+        
+        let guarantee = Self::get_guarantee(env.clone(), loan_id).expect("Loan has no guarantor");
+        
+        // Check if caller guarantees this loan
+        if guarantee.guarantor != guarantor {
+            panic!("Unauthorized");
+        }
+        
+        // Guarantor is only liable once the loan has defaulted, until it is written off
+        if !guarantee.liable || !matches!(loan.status, LoanStatus::Defaulted) {
+            panic!("Guarantor is not liable");
+        }
+        
+        if amount <= 0 {
+            panic!("Repayment amount must be positive");
+        }
+        
+        let payment = Self::apply_guarantor_payment(&env, loan_id, amount);
+        
+        // Shrink the liens on both streams to what is still owed, releasing them once settled
+        Self::sync_stream_liens(&env, &loan);
+        
+        payment
+    }
+    
+    /// Transfers part or all of a loan position to another owner
+    pub fn transfer_position(env: Env, from: Address, to: Address, loan_id: u32, share_bps: u32) {
+        // Require authorization from current owner
+        from.require_auth();
+        
+        if share_bps == 0 {
+            panic!("Share must be positive");
+        }
+        
+        let positions = Self::get_position_owner(env.clone(), loan_id);
+        if positions.is_empty() {
+            panic!("Loan has no positions");
+        }
+        
+        // Deduct from sender, dropping emptied positions
+        let mut updated = Vec::new(&env);
+        let mut found = false;
+        for position in positions.iter() {
+            if position.owner == from {
+                if position.share_bps < share_bps {
+                    panic!("Insufficient position share");
+                }
+                found = true;
+                if position.share_bps > share_bps {
+                    updated.push_back(LoanPosition { owner: from.clone(), share_bps: position.share_bps - share_bps });
+                }
+            } else {
+                updated.push_back(position);
+            }
+        }
+        if !found {
+            panic!("Not a position owner");
+        }
+        
+        // Credit to recipient, merging with an existing position
+        let mut merged = false;
+        for i in 0..updated.len() {
+            let mut position = updated.get(i).unwrap();
+            if position.owner == to {
+                position.share_bps += share_bps;
+                updated.set(i, position);
+                merged = true;
+            }
+        }
+        if !merged {
+            updated.push_back(LoanPosition { owner: to, share_bps });
+        }
+        
+        env.storage().persistent().set(&DataKey::LoanPositions(loan_id), &updated);
+    }
+    
+    /// Gets the current owners of a loan's lender position
+    pub fn get_position_owner(env: Env, loan_id: u32) -> Vec<LoanPosition> {
+        env.storage().persistent()
+            .get(&DataKey::LoanPositions(loan_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+    
+    /// Gets repayments routed to a position owner and not yet claimed
+    pub fn get_position_proceeds(env: Env, owner: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::PositionProceeds(owner)).unwrap_or(0)
+    }
+    
+    /// Claims all repayments routed to a position owner
+    /// Returns the amount claimed.
+    pub fn claim_position_proceeds(env: Env, owner: Address) -> i128 {
+        owner.require_auth();
+        
+        let amount = Self::get_position_proceeds(env.clone(), owner.clone());
+        env.storage().persistent().set(&DataKey::PositionProceeds(owner.clone()), &0i128);
+        
+        // Record transaction (not tied to a loan)
+        Self::record_transaction(&env, 0, TransactionType::PositionClaim, amount, owner);
+        
+        amount
+    }
+    
+    /// Gets the guarantee attached to a loan, if any
+    pub fn get_guarantee(env: Env, loan_id: u32) -> Option<Guarantee> {
+        env.storage().persistent().get(&DataKey::Guarantee(loan_id))
+    }
+    
+    /// Gets all loans guaranteed by an address
+    pub fn get_guarantor_loans(env: Env, guarantor: Address) -> Vec<u32> {
+        env.storage().persistent()
+            .get(&DataKey::GuarantorLoans(guarantor))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+    
+    /// Gets the total balance, including unpaid interest, an address guarantees across pending, approved and liable loans
+    pub fn get_guarantor_exposure(env: Env, guarantor: Address) -> i128 {
+        let loan_ids = Self::get_guarantor_loans(env.clone(), guarantor.clone());
+        
+        let mut exposure = 0i128;
+        
+        for loan_id in loan_ids.iter() {
+            let loan: Loan = env.storage().persistent()
+                .get(&DataKey::Loan(loan_id))
+                .unwrap();
+            let guarantee = Self::get_guarantee(env.clone(), loan_id).unwrap();
+            
+            let exposed = match loan.status {
+                LoanStatus::Pending | LoanStatus::Approved | LoanStatus::AtRisk => true,
+                LoanStatus::Defaulted => guarantee.liable, // Rejected loans release the guarantor
+                LoanStatus::Repaid | LoanStatus::Refinanced => false, // Refinanced guarantees move to the new loan
+                LoanStatus::WrittenOff => false,
+            };
+            if exposed {
+                // Guarantor is liable for unpaid interest too, accrued up to now
+                let mut loan = loan;
+                Self::accrue_interest(&env, &mut loan);
+                exposure += loan.amount - loan.repaid_amount + loan.accrued_interest;
+            }
+        }
+        
+        exposure
+    }
+    
+    /// Opens a revolving credit line against a salary stream
+    /// The line limit is reserved from the borrower's credit limit and can be drawn once approved.
+    /// The risk tier comes from the work profile contract when configured, ignoring `risk_tier`. Returns the line ID.
+    pub fn open_credit_line(
+        env: Env,
+        borrower: Address,
+        limit: i128,
+        risk_tier: u32,
+        collateral_stream_id: u32,
+    ) -> u32 {
+        // Require authorization from borrower
+        borrower.require_auth();
+        
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        let collateral_stream = Self::get_pledged_stream(&env, &streaming_contract, collateral_stream_id, &borrower);
+        let risk_tier = Self::assess_risk_tier(&env, &borrower, risk_tier);
+        
+        if limit <= 0 {
+            panic!("Credit line limit must be positive");
+        }
+        
+        // Line limit must fit under the aggregate credit limit
+        env.storage().persistent().set(&DataKey::BorrowerRiskTier(borrower.clone()), &risk_tier);
+        let credit = Self::calculate_credit_limit(&env, &borrower, Some(collateral_stream_id));
+        if limit > credit.available {
+            panic!("Credit limit exceeded");
+        }
+        
+        let interest_rate: u32 = env.storage().persistent()
+            .get(&DataKey::RiskMultiplier(risk_tier))
+            .unwrap_or(600u32);
+        
+        let employer = collateral_stream.employer;
+        
+        let line_id = Self::next_loan_id(&env);
+        let line = CreditLine {
+            id: line_id,
+            borrower: borrower.clone(),
+            limit,
+            drawn: 0,
+            accrued_interest: 0,
+            interest_paid: 0,
+            risk_tier,
+            interest_rate,
+            collateral_stream_id,
+            employer,
+            opened_at: env.ledger().timestamp(),
+            last_accrual: env.ledger().timestamp(),
+            is_open: true,
+            is_approved: false,
+        };
+        
+        env.storage().persistent().set(&DataKey::CreditLine(line_id), &line);
+        env.storage().persistent().set(&DataKey::LoanCounter, &line_id);
+        
+        let mut borrower_lines = Self::get_borrower_credit_lines(env.clone(), borrower.clone());
+        borrower_lines.push_back(line_id);
+        env.storage().persistent().set(&DataKey::BorrowerCreditLines(borrower), &borrower_lines);
+        
+        line_id
+    }
+    
+    /// Approves a credit line for drawing (admin function)
+    pub fn approve_credit_line(env: Env, admin: Address, line_id: u32) {
+        Self::require_admin(&env, &admin);
+        
+        let mut line: CreditLine = env.storage().persistent()
+            .get(&DataKey::CreditLine(line_id))
+            .expect("Credit line not found");
+        
+        if !line.is_open || line.is_approved {
+            panic!("Credit line is not pending");
+        }
+        Self::require_not_at_risk(&env, &line.borrower);
+        
+        // Reserved limits, this line included, must still fit under the credit limit
+        let credit = Self::calculate_credit_limit(&env, &line.borrower, None);
+        if credit.used > credit.limit {
+            panic!("Credit limit exceeded, cannot approve credit line");
+        }
+        
+        line.is_approved = true;
+        line.last_accrual = env.ledger().timestamp();
+        env.storage().persistent().set(&DataKey::CreditLine(line_id), &line);
+    }
+    
+    /// Draws from a credit line, up to its limit
+    pub fn draw(env: Env, borrower: Address, line_id: u32, amount: i128) -> i128 {
+        // Require authorization from borrower
+        borrower.require_auth();
+        
+        let mut line = Self::get_owned_credit_line(&env, &borrower, line_id);
+        
+        if amount <= 0 {
+            panic!("Draw amount must be positive");
+        }
+        if !line.is_approved {
+            panic!("Credit line is not approved");
+        }
+        Self::require_not_at_risk(&env, &borrower);
+        
+        Self::accrue_line_interest(&env, &mut line);
+        
+        if line.drawn + amount > line.limit {
+            panic!("Credit line limit exceeded");
+        }
+        
+        // Drawn balances count towards the employer's exposure
+        Self::check_employer_exposure(&env, &line.employer, amount);
+        Self::adjust_employer_exposure(&env, &line.employer, amount);
+        
+        line.drawn += amount;
+        env.storage().persistent().set(&DataKey::CreditLine(line_id), &line);
+        
+        // Record transaction
+        Self::record_transaction(&env, line_id, TransactionType::LineDraw, amount, borrower);
+        
+        amount
+    }
+    
+    /// Repays a credit line, interest first then drawn principal
+    pub fn repay_line(env: Env, borrower: Address, line_id: u32, amount: i128) -> i128 {
+        // Require authorization from borrower
+        borrower.require_auth();
+        
+        let mut line = Self::get_owned_credit_line(&env, &borrower, line_id);
+        
+        if amount <= 0 {
+            panic!("Repayment amount must be positive");
+        }
+        
+        Self::accrue_line_interest(&env, &mut line);
+        
+        // Calculate remaining amount including unpaid interest
+        let remaining = line.drawn + line.accrued_interest;
+        let payment = if amount > remaining { remaining } else { amount };
+        
+        let interest_payment = payment.min(line.accrued_interest);
+        line.accrued_interest -= interest_payment;
+        line.interest_paid += interest_payment;
+        line.drawn -= payment - interest_payment;
+        env.storage().persistent().set(&DataKey::CreditLine(line_id), &line);
+        Self::adjust_employer_exposure(&env, &line.employer, -(payment - interest_payment));
+        
+        // Record transaction
+        Self::record_transaction(&env, line_id, TransactionType::LineRepayment, payment, borrower.clone());
+        
+        Self::distribute_interest(&env, line_id, interest_payment, borrower);
+        
+        payment
+    }
+    
+    /// Closes a fully repaid credit line, releasing its limit
+    pub fn close_credit_line(env: Env, borrower: Address, line_id: u32) {
+        // Require authorization from borrower
+        borrower.require_auth();
+        
+        let mut line = Self::get_owned_credit_line(&env, &borrower, line_id);
+        
+        Self::accrue_line_interest(&env, &mut line);
+        
+        if line.drawn > 0 || line.accrued_interest > 0 {
+            panic!("Credit line has outstanding balance");
+        }
+        
+        line.is_open = false;
+        env.storage().persistent().set(&DataKey::CreditLine(line_id), &line);
+    }
+    
+    /// Gets a credit line with interest accrued up to now
+    pub fn get_line_state(env: Env, line_id: u32) -> CreditLine {
+        let mut line: CreditLine = env.storage().persistent()
+            .get(&DataKey::CreditLine(line_id))
+            .expect("Credit line not found");
+        
+        Self::accrue_line_interest(&env, &mut line);
+        
+        line
+    }
+    
+    /// Gets all credit lines for a borrower
+    pub fn get_borrower_credit_lines(env: Env, borrower: Address) -> Vec<u32> {
+        env.storage().persistent()
+            .get(&DataKey::BorrowerCreditLines(borrower))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+    
+    /// Gets a borrower's credit limit, usage and remaining availability
+    pub fn get_credit_line(env: Env, borrower: Address) -> CreditLimit {
+        Self::calculate_credit_limit(&env, &borrower, None)
+    }
+    
+    /// Gets outstanding loans for a borrower
+    pub fn get_outstanding_loans(env: Env, borrower: Address) -> i128 {
+        let loan_ids: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::BorrowerLoans(borrower))
+            .unwrap_or_else(|| Vec::new(&env));
+        
+        let mut total_outstanding = 0i128;
+        
+        for loan_id in loan_ids.iter() {
+            let loan: Loan = env.storage().persistent()
+                .get(&DataKey::Loan(loan_id))
+                .unwrap();
+            
+            if matches!(loan.status, LoanStatus::Approved | LoanStatus::AtRisk) {
+                total_outstanding += loan.amount - loan.repaid_amount;
+            }
+        }
+        
+        total_outstanding
+    }
+    
+    /// Gets loan details
+    pub fn get_loan(env: Env, loan_id: u32) -> Loan {
+        env.storage().persistent()
+            .get(&DataKey::Loan(loan_id))
+            .expect("Loan not found")
+    }
+    
+    /// Gets all loans for a borrower
+    pub fn get_borrower_loans(env: Env, borrower: Address) -> Vec<u32> {
+        env.storage().persistent()
             .get(&DataKey::BorrowerLoans(borrower))
             .unwrap_or_else(|| Vec::new(&env))
     }
@@ -357,7 +1778,10 @@ impl LendingContract {
                            (LoanStatus::Pending, LoanStatus::Pending) |
                            (LoanStatus::Approved, LoanStatus::Approved) |
                            (LoanStatus::Repaid, LoanStatus::Repaid) |
-                           (LoanStatus::Defaulted, LoanStatus::Defaulted)) {
+                           (LoanStatus::Defaulted, LoanStatus::Defaulted) |
+                           (LoanStatus::Refinanced, LoanStatus::Refinanced) |
+                           (LoanStatus::WrittenOff, LoanStatus::WrittenOff) |
+                           (LoanStatus::AtRisk, LoanStatus::AtRisk)) {
                     filtered_loans.push_back(loan);
                 }
             }
@@ -421,6 +1845,157 @@ impl LendingContract {
         borrower_transactions
     }
     
+    /// Gets a loan's balance movements between two timestamps, inclusive
+    /// Replays the loan's transaction log with the same interest accrual used for live loans.
+    pub fn get_loan_statement(env: Env, loan_id: u32, from_ts: u64, to_ts: u64) -> LoanStatement {
+        if from_ts > to_ts {
+            panic!("Invalid statement period");
+        }
+        
+        let loan = Self::get_loan(env.clone(), loan_id);
+        let mut statement = LoanStatement {
+            loan_id,
+            from_ts,
+            to_ts,
+            opening_balance: 0,
+            disbursed: 0,
+            fees: 0,
+            interest_accrued: 0,
+            payments: 0,
+            adjustments: 0,
+            closing_balance: 0,
+        };
+        
+        // Replayed loan state
+        let mut principal = 0i128;
+        let mut unpaid_interest = 0i128;
+        let mut total_interest = 0i128;
+        let mut last_accrual = 0u64;
+        let mut accruing = false;
+        let mut opened = false;
+        let mut opening_interest = 0i128;
+        
+        // Interest earned since the last replayed event, as of a point in time
+        let pending = |principal: i128, last_accrual: u64, accruing: bool, timestamp: u64| {
+            if accruing {
+                Self::interest_for(principal, loan.interest_rate, timestamp.saturating_sub(last_accrual))
+            } else {
+                0
+            }
+        };
+        
+        for transaction in Self::get_loan_transactions(env.clone(), loan_id).iter() {
+            if !opened && transaction.timestamp >= from_ts {
+                let interest = pending(principal, last_accrual, accruing, from_ts);
+                statement.opening_balance = principal + unpaid_interest + interest;
+                opening_interest = total_interest + interest;
+                opened = true;
+            }
+            if transaction.timestamp > to_ts {
+                break;
+            }
+            
+            // Accrue up to the event, as the contract did when it was recorded
+            if accruing {
+                let (interest, covered) = Self::accrual_for(principal, loan.interest_rate, transaction.timestamp.saturating_sub(last_accrual));
+                unpaid_interest += interest;
+                total_interest += interest;
+                last_accrual += covered;
+            } else {
+                last_accrual = transaction.timestamp;
+            }
+            
+            let amount = transaction.amount;
+            let (mut disbursed, mut fees, mut payments, mut adjustments) = (0i128, 0i128, 0i128, 0i128);
+            match transaction.transaction_type {
+                TransactionType::LoanApproval => {
+                    principal += amount;
+                    accruing = true;
+                    // A refinancing loan takes over the old balance instead of paying out funds
+                    if loan.previous_loan_id.is_some() {
+                        adjustments = -amount;
+                    } else {
+                        disbursed = amount;
+                    }
+                },
+                TransactionType::OriginationFee => {
+                    disbursed = -amount;
+                    fees = amount;
+                },
+                TransactionType::Repayment | TransactionType::GuarantorRepayment | TransactionType::Recovery => {
+                    // Interest is paid first, then principal
+                    let interest_payment = amount.min(unpaid_interest);
+                    unpaid_interest -= interest_payment;
+                    principal -= amount - interest_payment;
+                    payments = amount;
+                },
+                TransactionType::Default => accruing = false,
+                TransactionType::Refinance => {
+                    adjustments = principal + unpaid_interest;
+                    principal = 0;
+                    unpaid_interest = 0;
+                    accruing = false;
+                },
+                TransactionType::WriteOff => {
+                    principal = 0;
+                    unpaid_interest = 0;
+                    adjustments = amount;
+                },
+                _ => {},
+            }
+            
+            if opened {
+                statement.disbursed += disbursed;
+                statement.fees += fees;
+                statement.payments += payments;
+                statement.adjustments += adjustments;
+            }
+        }
+        
+        if !opened {
+            let interest = pending(principal, last_accrual, accruing, from_ts);
+            statement.opening_balance = principal + unpaid_interest + interest;
+            opening_interest = total_interest + interest;
+        }
+        
+        let interest = pending(principal, last_accrual, accruing, to_ts);
+        statement.closing_balance = principal + unpaid_interest + interest;
+        statement.interest_accrued = total_interest + interest - opening_interest;
+        
+        statement
+    }
+    
+    /// Gets balance movements across all loans of a borrower between two timestamps, inclusive
+    pub fn get_borrower_statement(env: Env, borrower: Address, from_ts: u64, to_ts: u64) -> BorrowerStatement {
+        let mut statement = BorrowerStatement {
+            borrower: borrower.clone(),
+            from_ts,
+            to_ts,
+            opening_balance: 0,
+            disbursed: 0,
+            fees: 0,
+            interest_accrued: 0,
+            payments: 0,
+            adjustments: 0,
+            closing_balance: 0,
+            loans: Vec::new(&env),
+        };
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), borrower).iter() {
+            let loan_statement = Self::get_loan_statement(env.clone(), loan_id, from_ts, to_ts);
+            statement.opening_balance += loan_statement.opening_balance;
+            statement.disbursed += loan_statement.disbursed;
+            statement.fees += loan_statement.fees;
+            statement.interest_accrued += loan_statement.interest_accrued;
+            statement.payments += loan_statement.payments;
+            statement.adjustments += loan_statement.adjustments;
+            statement.closing_balance += loan_statement.closing_balance;
+            statement.loans.push_back(loan_statement);
+        }
+        
+        statement
+    }
+    
     /// Gets loan summary statistics
     pub fn get_loan_summary(env: Env) -> LoanSummary {
         let loan_ids: Vec<u32> = env.storage().persistent()
@@ -433,12 +2008,17 @@ impl LendingContract {
         let mut approved_loans = 0u32;
         let mut repaid_loans = 0u32;
         let mut defaulted_loans = 0u32;
+        let mut refinanced_loans = 0u32;
+        let mut written_off_loans = 0u32;
+        let mut at_risk_loans = 0u32;
         let mut total_outstanding = 0i128;
+        let mut total_recovered = 0i128;
         
         for loan_id in loan_ids.iter() {
             if let Some(loan) = env.storage().persistent().get::<DataKey, Loan>(&DataKey::Loan(loan_id)) {
                 total_loans += 1;
                 total_amount += loan.amount;
+                total_recovered += loan.recovered_amount;
                 
                 match loan.status {
                     LoanStatus::Pending => pending_loans += 1,
@@ -448,6 +2028,12 @@ impl LendingContract {
                     },
                     LoanStatus::Repaid => repaid_loans += 1,
                     LoanStatus::Defaulted => defaulted_loans += 1,
+                    LoanStatus::Refinanced => refinanced_loans += 1,
+                    LoanStatus::WrittenOff => written_off_loans += 1,
+                    LoanStatus::AtRisk => {
+                        at_risk_loans += 1;
+                        total_outstanding += loan.amount - loan.repaid_amount;
+                    },
                 }
             }
         }
@@ -459,8 +2045,42 @@ impl LendingContract {
             approved_loans,
             repaid_loans,
             defaulted_loans,
+            refinanced_loans,
+            written_off_loans,
+            at_risk_loans,
             total_outstanding,
+            total_recovered,
+            total_written_off: env.storage().persistent().get(&DataKey::TotalWrittenOff).unwrap_or(0),
+            reserve_balance: Self::get_reserve_balance(env.clone()),
+        }
+    }
+    
+    /// Gets portfolio aggregates for each risk tier
+    pub fn get_portfolio_breakdown(env: Env) -> Vec<PortfolioBreakdown> {
+        let mut breakdown = Vec::new(&env);
+        
+        for risk_tier in 1..=5u32 {
+            let stats = Self::get_portfolio_stats(&env, &DataKey::TierStats(risk_tier));
+            breakdown.push_back(Self::to_breakdown(risk_tier, stats));
+        }
+        
+        breakdown
+    }
+    
+    /// Gets portfolio aggregates for each origination month, oldest first
+    pub fn get_cohort_breakdown(env: Env) -> Vec<PortfolioBreakdown> {
+        let cohorts: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::Cohorts)
+            .unwrap_or_else(|| Vec::new(&env));
+        
+        let mut breakdown = Vec::new(&env);
+        
+        for month in cohorts.iter() {
+            let stats = Self::get_portfolio_stats(&env, &DataKey::CohortStats(month));
+            breakdown.push_back(Self::to_breakdown(month, stats));
         }
+        
+        breakdown
     }
     
     /// Calculates maximum loan amount based on risk tier
@@ -475,6 +2095,549 @@ impl LendingContract {
         }
     }
     
+    /// Internal function to load running portfolio aggregates
+    fn get_portfolio_stats(env: &Env, key: &DataKey) -> PortfolioStats {
+        env.storage().persistent().get(key).unwrap_or(PortfolioStats {
+            loans: 0,
+            principal: 0,
+            outstanding: 0,
+            repaid: 0,
+            defaulted: 0,
+            rate_weighted_principal: 0,
+        })
+    }
+    
+    /// Internal function to apply a change to the tier and cohort aggregates of an originated loan
+    fn update_portfolio(env: &Env, loan: &Loan, apply: impl Fn(&mut PortfolioStats)) {
+        let month = Self::origination_month(loan.approved_at);
+        
+        for key in [DataKey::TierStats(loan.risk_tier), DataKey::CohortStats(month)] {
+            let mut stats = Self::get_portfolio_stats(env, &key);
+            apply(&mut stats);
+            env.storage().persistent().set(&key, &stats);
+        }
+    }
+    
+    /// Internal function to add a newly approved loan to the portfolio aggregates
+    fn record_origination(env: &Env, loan: &Loan) {
+        let month = Self::origination_month(loan.approved_at);
+        let mut cohorts: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::Cohorts)
+            .unwrap_or_else(|| Vec::new(env));
+        if !cohorts.contains(month) {
+            cohorts.push_back(month);
+            env.storage().persistent().set(&DataKey::Cohorts, &cohorts);
+        }
+        
+        Self::update_portfolio(env, loan, |stats| {
+            stats.loans += 1;
+            stats.principal += loan.amount;
+            stats.outstanding += loan.amount;
+            stats.rate_weighted_principal += loan.amount * loan.interest_rate as i128;
+        });
+        Self::adjust_employer_exposure(env, &loan.employer, loan.amount);
+    }
+    
+    /// Internal function to check the caller is the stored admin
+    fn require_admin(env: &Env, admin: &Address) {
+        admin.require_auth();
+        
+        let stored_admin: Address = env.storage().persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized");
+        if stored_admin != *admin {
+            panic!("Unauthorized");
+        }
+    }
+    
+    /// Internal function to block new credit for a borrower flagged at risk
+    fn require_not_at_risk(env: &Env, borrower: &Address) {
+        if Self::is_borrower_at_risk(env.clone(), borrower.clone()) {
+            panic!("Borrower is at risk");
+        }
+    }
+    
+    /// Internal function to fail with a typed error if new credit would breach an employer's exposure limit
+    fn check_employer_exposure(env: &Env, employer: &Address, amount: i128) {
+        let max_exposure: Option<i128> = env.storage().persistent().get(&DataKey::MaxEmployerExposure);
+        
+        if let Some(max_exposure) = max_exposure {
+            if Self::get_employer_exposure(env.clone(), employer.clone()) + amount > max_exposure {
+                panic_with_error!(env, LendingError::EmployerExposureExceeded);
+            }
+        }
+    }
+    
+    /// Internal function to change an employer's outstanding exposure
+    fn adjust_employer_exposure(env: &Env, employer: &Address, delta: i128) {
+        let exposure = Self::get_employer_exposure(env.clone(), employer.clone());
+        env.storage().persistent().set(&DataKey::EmployerExposure(employer.clone()), &(exposure + delta));
+    }
+    
+    /// Internal function to derive rates from running aggregates
+    fn to_breakdown(key: u32, stats: PortfolioStats) -> PortfolioBreakdown {
+        let default_rate = (stats.defaulted * 10_000).checked_div(stats.loans).unwrap_or(0);
+        let weighted_average_rate = stats.rate_weighted_principal.checked_div(stats.principal).unwrap_or(0) as u32;
+        
+        PortfolioBreakdown {
+            key,
+            loans: stats.loans,
+            principal: stats.principal,
+            outstanding: stats.outstanding,
+            repaid: stats.repaid,
+            defaulted: stats.defaulted,
+            default_rate,
+            weighted_average_rate,
+        }
+    }
+    
+    /// Internal function to convert a timestamp to its calendar month as YYYYMM
+    fn origination_month(timestamp: u64) -> u32 {
+        // Civil-from-days conversion for the proleptic Gregorian calendar
+        let z = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        
+        (year * 100 + month) as u32
+    }
+    
+    /// Internal function to accrue simple interest on the outstanding principal of an approved loan
+    /// Only the time that earned whole units of interest is consumed; the rest carries to the next accrual.
+    fn accrue_interest(env: &Env, loan: &mut Loan) {
+        let now = env.ledger().timestamp();
+        
+        if matches!(loan.status, LoanStatus::Approved | LoanStatus::AtRisk) {
+            let principal = loan.amount - loan.repaid_amount;
+            let (interest, covered) = Self::accrual_for(principal, loan.interest_rate, now.saturating_sub(loan.last_accrual));
+            loan.accrued_interest += interest;
+            loan.last_accrual += covered;
+        } else {
+            loan.last_accrual = now;
+        }
+    }
+    
+    /// Internal function to calculate simple interest on a principal over elapsed seconds
+    fn interest_for(principal: i128, interest_rate: u32, elapsed: u64) -> i128 {
+        principal * interest_rate as i128 * elapsed as i128 / (10_000 * SECONDS_PER_YEAR as i128)
+    }
+    
+    /// Internal function to calculate interest over elapsed seconds and the seconds that earned it
+    /// The fraction of a unit lost to rounding stays unconsumed instead of being forgiven.
+    fn accrual_for(principal: i128, interest_rate: u32, elapsed: u64) -> (i128, u64) {
+        let yearly = principal * interest_rate as i128;
+        if yearly <= 0 {
+            return (0, elapsed);
+        }
+        
+        let interest = Self::interest_for(principal, interest_rate, elapsed);
+        let covered = (interest * (10_000 * SECONDS_PER_YEAR as i128) + yearly - 1) / yearly;
+        (interest, covered as u64)
+    }
+    
+    /// Internal function to accrue interest on a credit line's drawn balance for each full day elapsed
+    /// Days too short to earn a whole unit on small balances carry to the next accrual.
+    fn accrue_line_interest(env: &Env, line: &mut CreditLine) {
+        let days = env.ledger().timestamp().saturating_sub(line.last_accrual) / SECONDS_PER_DAY;
+        
+        let (interest, covered) = Self::accrual_for(line.drawn, line.interest_rate, days * SECONDS_PER_DAY);
+        line.accrued_interest += interest;
+        line.last_accrual += covered;
+    }
+    
+    /// Internal function to load an open credit line and check the borrower owns it
+    fn get_owned_credit_line(env: &Env, borrower: &Address, line_id: u32) -> CreditLine {
+        let line: CreditLine = env.storage().persistent()
+            .get(&DataKey::CreditLine(line_id))
+            .expect("Credit line not found");
+        
+        if line.borrower != *borrower {
+            panic!("Unauthorized");
+        }
+        
+        if !line.is_open {
+            panic!("Credit line is closed");
+        }
+        
+        line
+    }
+    
+    /// Internal function to split interest paid between the loss reserve and the protocol
+    /// Returns the amount retained, the rest belongs to lenders.
+    fn distribute_interest(env: &Env, loan_id: u32, interest_payment: i128, borrower: Address) -> i128 {
+        let reserve_bps: u32 = env.storage().persistent().get(&DataKey::ReserveRate).unwrap_or(0);
+        let reserve_share = interest_payment * reserve_bps as i128 / 10_000;
+        if reserve_share > 0 {
+            let reserve = Self::get_reserve_balance(env.clone());
+            env.storage().persistent().set(&DataKey::ReserveBalance, &(reserve + reserve_share));
+        }
+        
+        let protocol_fee_bps = Self::get_fee_config(env.clone()).map_or(0, |config| config.protocol_fee_bps);
+        let protocol_fee = interest_payment * protocol_fee_bps as i128 / 10_000;
+        if protocol_fee > 0 {
+            Self::add_fee(env, protocol_fee);
+            Self::record_transaction(env, loan_id, TransactionType::ProtocolFee, protocol_fee, borrower);
+        }
+        
+        reserve_share + protocol_fee
+    }
+    
+    /// Internal function to credit lender proceeds of a loan pro rata to its position owners
+    /// Rounding remainder goes to the last owner.
+    fn route_to_positions(env: &Env, loan_id: u32, amount: i128) {
+        let positions = Self::get_position_owner(env.clone(), loan_id);
+        if amount <= 0 || positions.is_empty() {
+            return;
+        }
+        
+        let mut remaining = amount;
+        for (i, position) in positions.iter().enumerate() {
+            let share = if i as u32 == positions.len() - 1 {
+                remaining
+            } else {
+                amount * position.share_bps as i128 / 10_000
+            };
+            remaining -= share;
+            
+            let proceeds = Self::get_position_proceeds(env.clone(), position.owner.clone());
+            env.storage().persistent().set(&DataKey::PositionProceeds(position.owner), &(proceeds + share));
+        }
+    }
+    
+    /// Internal function to split a balance into equal monthly installments (rounded up)
+    fn calculate_installment(amount: i128, term_months: u32) -> i128 {
+        let term = term_months.max(1) as i128;
+        (amount + term - 1) / term
+    }
+    
+    /// Internal function to get the principal scheduled to be repaid by now, one installment per elapsed month
+    fn principal_due(env: &Env, loan: &Loan) -> i128 {
+        let elapsed_months = env.ledger().timestamp().saturating_sub(loan.approved_at) / SECONDS_PER_MONTH;
+        // Accelerated installments are asked of an at-risk borrower, not owed retroactively
+        let installment = loan.scheduled_installment.unwrap_or(loan.installment_amount);
+        (installment * elapsed_months as i128).min(loan.amount)
+    }
+    
+    /// Internal function to report a borrower's repayment behaviour to the work profile contract, if configured
+    fn report_credit_event(env: &Env, borrower: &Address, event: CreditEvent) {
+        if let Some(work_profile_contract) = Self::get_work_profile_contract(env.clone()) {
+            WorkProfileClient::new(env, &work_profile_contract)
+                .report_credit_event(&env.current_contract_address(), borrower, &event);
+        }
+    }
+    
+    /// Internal function to get the risk tier a borrower is priced at
+    /// The work profile contract's tier is authoritative when configured, and no credit is priced
+    /// while the borrower disputes their profile. Otherwise the requested tier stands, subject to
+    /// admin approval of the loan or line.
+    fn assess_risk_tier(env: &Env, borrower: &Address, requested_tier: u32) -> u32 {
+        let risk_tier = match Self::get_work_profile_contract(env.clone()) {
+            Some(work_profile_contract) => {
+                let work_profile = WorkProfileClient::new(env, &work_profile_contract);
+                if work_profile.is_profile_disputed(borrower) {
+                    panic!("Work profile is disputed");
+                }
+                work_profile.get_employee_risk_tier(borrower)
+            },
+            None => requested_tier,
+        };
+        
+        if !(1..=5).contains(&risk_tier) {
+            panic!("Invalid risk tier");
+        }
+        
+        risk_tier
+    }
+    
+    /// Internal function to load a stream pledged as collateral
+    /// The stream must be active and paid to the pledger, so a lien at default claims their own wages.
+    fn get_pledged_stream(env: &Env, streaming_contract: &Address, stream_id: u32, pledger: &Address) -> SalaryStream {
+        let stream = SalaryStreamingClient::new(env, streaming_contract).get_stream(&stream_id);
+        if stream.employee != *pledger {
+            panic!("Stream does not belong to pledger");
+        }
+        if !stream.is_active {
+            panic!("Pledged stream is not active");
+        }
+        stream
+    }
+    
+    /// Internal function to get a product's interest rate for a risk tier
+    fn product_rate(env: &Env, product: &LoanProduct, risk_tier: u32) -> u32 {
+        let tier_rate: u32 = env.storage().persistent()
+            .get(&DataKey::RiskMultiplier(risk_tier))
+            .unwrap_or(600u32);
+        tier_rate + product.rate_spreads.get(risk_tier - 1).unwrap_or(0)
+    }
+    
+    /// Internal function to allocate the next loan ID
+    fn next_loan_id(env: &Env) -> u32 {
+        let counter: u32 = env.storage().persistent().get(&DataKey::LoanCounter).unwrap_or(0);
+        counter + 1
+    }
+    
+    /// Internal function to store a new loan and add it to the borrower and global lists
+    fn store_new_loan(env: &Env, loan: &Loan) {
+        env.storage().persistent().set(&DataKey::Loan(loan.id), loan);
+        env.storage().persistent().set(&DataKey::LoanCounter, &loan.id);
+        
+        // Add to borrower's loan list
+        let mut borrower_loans: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::BorrowerLoans(loan.borrower.clone()))
+            .unwrap_or_else(|| Vec::new(env));
+        borrower_loans.push_back(loan.id);
+        env.storage().persistent().set(&DataKey::BorrowerLoans(loan.borrower.clone()), &borrower_loans);
+        
+        // Add to all loans list
+        let mut all_loans: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::AllLoanIds)
+            .unwrap_or_else(|| Vec::new(env));
+        all_loans.push_back(loan.id);
+        env.storage().persistent().set(&DataKey::AllLoanIds, &all_loans);
+    }
+    
+    /// Internal function to store a guarantee and add the loan to the guarantor's list
+    fn store_guarantee(env: &Env, guarantee: &Guarantee) {
+        env.storage().persistent().set(&DataKey::Guarantee(guarantee.loan_id), guarantee);
+        
+        let mut guarantor_loans: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::GuarantorLoans(guarantee.guarantor.clone()))
+            .unwrap_or_else(|| Vec::new(env));
+        guarantor_loans.push_back(guarantee.loan_id);
+        env.storage().persistent().set(&DataKey::GuarantorLoans(guarantee.guarantor.clone()), &guarantor_loans);
+    }
+    
+    /// Internal function to derive a borrower's credit limit from risk tier and pledged stream value
+    /// The tier is the work profile's current tier when configured, otherwise the last one requested.
+    /// Only the borrower's own streams count; a guarantor's pledge secures recovery but doesn't raise the limit.
+    /// `extra_stream_id` counts a stream about to be pledged by a new request.
+    fn calculate_credit_limit(env: &Env, borrower: &Address, extra_stream_id: Option<u32>) -> CreditLimit {
+        let requested_tier: u32 = env.storage().persistent()
+            .get(&DataKey::BorrowerRiskTier(borrower.clone()))
+            .unwrap_or(5);
+        let risk_tier = Self::assess_risk_tier(env, borrower, requested_tier);
+        
+        let streaming_contract = Self::get_streaming_contract(env.clone())
+            .expect("Streaming contract not configured");
+        let streaming = SalaryStreamingClient::new(env, &streaming_contract);
+        
+        // Collect distinct streams pledged on open loans and sum balances in use
+        let mut stream_ids: Vec<u32> = Vec::new(env);
+        if let Some(stream_id) = extra_stream_id {
+            stream_ids.push_back(stream_id);
+        }
+        let mut used = 0i128;
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), borrower.clone()).iter() {
+            let loan = Self::get_loan(env.clone(), loan_id);
+            match loan.status {
+                LoanStatus::Pending => used += loan.amount,
+                LoanStatus::Approved | LoanStatus::AtRisk => used += loan.amount - loan.repaid_amount,
+                _ => continue,
+            }
+            if !stream_ids.contains(loan.collateral_stream_id) {
+                stream_ids.push_back(loan.collateral_stream_id);
+            }
+        }
+        
+        // Open credit lines reserve their full limit
+        for line_id in Self::get_borrower_credit_lines(env.clone(), borrower.clone()).iter() {
+            let line: CreditLine = env.storage().persistent()
+                .get(&DataKey::CreditLine(line_id))
+                .unwrap();
+            if !line.is_open {
+                continue;
+            }
+            used += line.limit;
+            if !stream_ids.contains(line.collateral_stream_id) {
+                stream_ids.push_back(line.collateral_stream_id);
+            }
+        }
+        
+        // Only the unpaid remainder of active streams backs new credit
+        let mut pledged_value = 0i128;
+        for stream_id in stream_ids.iter() {
+            let stream = streaming.get_stream(&stream_id);
+            if stream.is_active && stream.employee == *borrower {
+                pledged_value += stream.total_amount - stream.withdrawn_amount;
+            }
+        }
+        
+        let max_percentage = Self::get_max_loan_percentage(env.clone(), risk_tier);
+        let limit = pledged_value * max_percentage as i128 / 100;
+        
+        CreditLimit {
+            risk_tier,
+            pledged_value,
+            limit,
+            used,
+            available: (limit - used).max(0),
+        }
+    }
+    
+    /// Internal function to sum unrepaid advances (including fees) on a stream
+    fn outstanding_advances(env: &Env, stream_id: u32) -> i128 {
+        let advance_ids: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::StreamAdvances(stream_id))
+            .unwrap_or_else(|| Vec::new(env));
+        
+        let mut outstanding = 0i128;
+        for advance_id in advance_ids.iter() {
+            let advance = Self::get_advance(env.clone(), advance_id);
+            if !advance.is_repaid {
+                outstanding += advance.amount + advance.fee - advance.repaid_amount;
+            }
+        }
+        
+        outstanding
+    }
+    
+    /// Internal function to apply wages collected from a stream's lien
+    /// Advances on the stream are repaid first, oldest first, then defaulted loans it collateralizes,
+    /// then defaulted loans it was pledged to guarantee.
+    fn apply_lien_collection(env: &Env, stream_id: u32, borrower: &Address, amount: i128) {
+        let mut remaining = amount;
+        
+        let advance_ids: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::StreamAdvances(stream_id))
+            .unwrap_or_else(|| Vec::new(env));
+        
+        for advance_id in advance_ids.iter() {
+            if remaining <= 0 {
+                break;
+            }
+            
+            let mut advance = Self::get_advance(env.clone(), advance_id);
+            if advance.is_repaid {
+                continue;
+            }
+            
+            let due = advance.amount + advance.fee - advance.repaid_amount;
+            let payment = remaining.min(due);
+            
+            // Advanced amount is repaid before the fee
+            let fee_payment = (advance.repaid_amount + payment - advance.amount).max(0).min(payment);
+            advance.repaid_amount += payment;
+            advance.is_repaid = advance.repaid_amount >= advance.amount + advance.fee;
+            env.storage().persistent().set(&DataKey::Advance(advance_id), &advance);
+            remaining -= payment;
+            
+            Self::adjust_employer_exposure(env, &advance.employer, -(payment - fee_payment));
+            Self::record_transaction(env, advance_id, TransactionType::AdvanceRepayment, payment, advance.borrower.clone());
+            Self::distribute_interest(env, advance_id, fee_payment, advance.borrower);
+        }
+        
+        for loan_id in Self::get_borrower_loans(env.clone(), borrower.clone()).iter() {
+            if remaining <= 0 {
+                break;
+            }
+            
+            let mut loan = Self::get_loan(env.clone(), loan_id);
+            if loan.collateral_stream_id != stream_id || !matches!(loan.status, LoanStatus::Defaulted) {
+                continue;
+            }
+            
+            let recovered = remaining.min(loan.amount - loan.repaid_amount + loan.accrued_interest);
+            if recovered <= 0 {
+                continue;
+            }
+            
+            // Unpaid interest is recovered first, then principal
+            let interest_payment = recovered.min(loan.accrued_interest);
+            loan.accrued_interest -= interest_payment;
+            loan.interest_paid += interest_payment;
+            loan.repaid_amount += recovered - interest_payment;
+            loan.recovered_amount += recovered;
+            Self::update_portfolio(env, &loan, |stats| stats.repaid += recovered - interest_payment);
+            env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+            remaining -= recovered;
+            
+            // Record transaction
+            Self::record_transaction(env, loan_id, TransactionType::Recovery, recovered, loan.borrower.clone());
+            
+            let retained = Self::distribute_interest(env, loan_id, interest_payment, loan.borrower);
+            Self::route_to_positions(env, loan_id, recovered - retained);
+        }
+        
+        // What is left goes to defaulted loans guaranteed with the stream
+        for loan_id in Self::get_guarantor_loans(env.clone(), borrower.clone()).iter() {
+            if remaining <= 0 {
+                break;
+            }
+            
+            let guarantee = Self::get_guarantee(env.clone(), loan_id).unwrap();
+            let loan = Self::get_loan(env.clone(), loan_id);
+            if !guarantee.liable
+                || !matches!(guarantee.collateral, GuarantorCollateral::Stream(id) if id == stream_id)
+                || !matches!(loan.status, LoanStatus::Defaulted)
+            {
+                continue;
+            }
+            
+            remaining -= Self::apply_guarantor_payment(env, loan_id, remaining);
+        }
+    }
+    
+    /// Internal function to apply a guarantor's payment to a defaulted loan, unpaid interest first
+    /// Returns the amount applied.
+    fn apply_guarantor_payment(env: &Env, loan_id: u32, amount: i128) -> i128 {
+        let mut loan = Self::get_loan(env.clone(), loan_id);
+        let mut guarantee = Self::get_guarantee(env.clone(), loan_id).unwrap();
+        
+        // Calculate remaining amount including unpaid interest
+        let remaining = loan.amount - loan.repaid_amount + loan.accrued_interest;
+        let payment = if amount > remaining { remaining } else { amount };
+        
+        // Interest is paid first, then principal
+        let interest_payment = payment.min(loan.accrued_interest);
+        loan.accrued_interest -= interest_payment;
+        loan.interest_paid += interest_payment;
+        loan.repaid_amount += payment - interest_payment;
+        guarantee.paid_amount += payment;
+        Self::update_portfolio(env, &loan, |stats| stats.repaid += payment - interest_payment);
+        
+        env.storage().persistent().set(&DataKey::Loan(loan_id), &loan);
+        env.storage().persistent().set(&DataKey::Guarantee(loan_id), &guarantee);
+        
+        // Record transaction
+        Self::record_transaction(env, loan_id, TransactionType::GuarantorRepayment, payment, loan.borrower.clone());
+        
+        let retained = Self::distribute_interest(env, loan_id, interest_payment, loan.borrower);
+        Self::route_to_positions(env, loan_id, payment - retained);
+        
+        payment
+    }
+    
+    /// Internal function to resize the liens on a loan's collateral stream and its guarantor's
+    /// pledged stream to what each stream still owes, releasing those with nothing left to claim
+    fn sync_stream_liens(env: &Env, loan: &Loan) {
+        Self::sync_lien(env, loan.collateral_stream_id, &loan.borrower);
+        
+        if let Some(guarantee) = Self::get_guarantee(env.clone(), loan.id) {
+            let GuarantorCollateral::Stream(stream_id) = guarantee.collateral;
+            Self::sync_lien(env, stream_id, &guarantee.guarantor);
+        }
+    }
+    
+    /// Internal function to resize the lien on an employee's stream to the stream's remaining claims
+    fn sync_lien(env: &Env, stream_id: u32, employee: &Address) {
+        if let Some(streaming_contract) = Self::get_streaming_contract(env.clone()) {
+            let claim = Self::get_stream_claim(env.clone(), stream_id, employee.clone());
+            let streaming = SalaryStreamingClient::new(env, &streaming_contract);
+            let _ = streaming.try_adjust_lien(&env.current_contract_address(), &stream_id, &claim);
+        }
+    }
+    
+    /// Internal function to add to the unclaimed protocol fee balance
+    fn add_fee(env: &Env, amount: i128) {
+        let balance = Self::get_fee_balance(env.clone());
+        env.storage().persistent().set(&DataKey::FeeBalance, &(balance + amount));
+    }
+    
     /// Internal function to record transactions
     fn record_transaction(
         env: &Env,
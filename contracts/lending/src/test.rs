@@ -1,24 +1,227 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env};
+use soroban_sdk::{testutils::{Address as _, Ledger as _}, vec, Env};
+
+mod mock_streaming {
+    use crate::SalaryStream;
+    use soroban_sdk::{contract, contractimpl, contracttype, Address, Env};
+
+    #[derive(Clone)]
+    #[contracttype]
+    pub enum MockKey {
+        Stream(u32),
+        Available(u32),
+        Lien(u32),
+    }
+
+    /// Minimal stand-in for the salary streaming contract's stream and lien interface
+    #[contract]
+    pub struct MockStreamingContract;
+
+    #[contractimpl]
+    impl MockStreamingContract {
+        pub fn set_stream(env: Env, stream_id: u32, employee: Address, total_amount: i128) {
+            let stream = SalaryStream {
+                id: stream_id,
+                employer: employee.clone(),
+                employee,
+                total_amount,
+                rate_per_second: 1,
+                start_time: 0,
+                duration_seconds: total_amount as u64,
+                withdrawn_amount: 0,
+                is_active: true,
+                is_paused: false,
+            };
+            env.storage().persistent().set(&MockKey::Stream(stream_id), &stream);
+        }
+
+        pub fn set_employer(env: Env, stream_id: u32, employer: Address) {
+            let mut stream = Self::get_stream(env.clone(), stream_id);
+            stream.employer = employer;
+            env.storage().persistent().set(&MockKey::Stream(stream_id), &stream);
+        }
+
+        pub fn get_stream(env: Env, stream_id: u32) -> SalaryStream {
+            env.storage().persistent().get(&MockKey::Stream(stream_id)).expect("Stream not found")
+        }
+
+        pub fn set_available(env: Env, stream_id: u32, amount: i128) {
+            env.storage().persistent().set(&MockKey::Available(stream_id), &amount);
+        }
+
+        pub fn get_lien(env: Env, stream_id: u32) -> i128 {
+            env.storage().persistent().get(&MockKey::Lien(stream_id)).unwrap_or(0)
+        }
+
+        pub fn calculate_available(env: Env, stream_id: u32) -> i128 {
+            env.storage().persistent().get(&MockKey::Available(stream_id)).unwrap_or(0)
+        }
+
+        pub fn place_lien(env: Env, lender: Address, stream_id: u32, amount: i128) {
+            lender.require_auth();
+            let lien = Self::get_lien(env.clone(), stream_id) + amount;
+            env.storage().persistent().set(&MockKey::Lien(stream_id), &lien);
+        }
+
+        pub fn collect_lien(env: Env, lender: Address, stream_id: u32, max_amount: i128) -> i128 {
+            lender.require_auth();
+            let available: i128 = env.storage().persistent().get(&MockKey::Available(stream_id)).unwrap_or(0);
+            let lien = Self::get_lien(env.clone(), stream_id);
+            let amount = available.min(lien).min(max_amount);
+            env.storage().persistent().set(&MockKey::Available(stream_id), &(available - amount));
+            env.storage().persistent().set(&MockKey::Lien(stream_id), &(lien - amount));
+            amount
+        }
+
+        pub fn release_lien(env: Env, lender: Address, stream_id: u32) {
+            lender.require_auth();
+            env.storage().persistent().set(&MockKey::Lien(stream_id), &0i128);
+        }
+
+        pub fn adjust_lien(env: Env, lender: Address, stream_id: u32, remaining: i128) {
+            lender.require_auth();
+            if Self::get_lien(env.clone(), stream_id) <= 0 {
+                panic!("No lien on stream");
+            }
+            env.storage().persistent().set(&MockKey::Lien(stream_id), &remaining.max(0));
+        }
+
+        /// Pays the lien from the employee's accrued wages as a withdrawal would, capped at the lender's claim
+        pub fn withdraw(env: Env, lending: Address, stream_id: u32) -> i128 {
+            let lien = Self::get_lien(env.clone(), stream_id);
+            if lien <= 0 {
+                return 0;
+            }
+            let employee = Self::get_stream(env.clone(), stream_id).employee;
+            let lending = crate::LendingContractClient::new(&env, &lending);
+            let claim = lending.get_stream_claim(&stream_id, &employee);
+            let available = Self::calculate_available(env.clone(), stream_id);
+            let amount = available.min(lien).min(claim).max(0);
+            env.storage().persistent().set(&MockKey::Available(stream_id), &(available - amount));
+            let lien = if amount >= claim { 0 } else { lien - amount };
+            env.storage().persistent().set(&MockKey::Lien(stream_id), &lien);
+            if amount > 0 {
+                lending.on_lien_collected(&stream_id, &employee, &amount);
+            }
+            amount
+        }
+    }
+}
+
+mod mock_work_profile {
+    use crate::CreditEvent;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Symbol, Vec};
+
+    const EVENTS: Symbol = symbol_short!("EVENTS");
+    const TIER: Symbol = symbol_short!("TIER");
+    const DISPUTED: Symbol = symbol_short!("DISPUTED");
+
+    /// Minimal stand-in for the work profile contract recording credit events, verification, tiers and disputes
+    #[contract]
+    pub struct MockWorkProfileContract;
+
+    #[contractimpl]
+    impl MockWorkProfileContract {
+        pub fn report_credit_event(env: Env, reporter: Address, _employee: Address, event: CreditEvent) {
+            reporter.require_auth();
+            let mut events = Self::get_events(env.clone());
+            events.push_back(event);
+            env.storage().persistent().set(&EVENTS, &events);
+        }
+
+        pub fn get_events(env: Env) -> Vec<CreditEvent> {
+            env.storage().persistent().get(&EVENTS).unwrap_or_else(|| Vec::new(&env))
+        }
+
+        pub fn set_verified(env: Env, employee: Address, verified: bool) {
+            env.storage().persistent().set(&employee, &verified);
+        }
+
+        pub fn is_profile_verified(env: Env, employee: Address) -> bool {
+            env.storage().persistent().get(&employee).unwrap_or(false)
+        }
+
+        pub fn set_tier(env: Env, employee: Address, tier: u32) {
+            env.storage().persistent().set(&(TIER, employee), &tier);
+        }
+
+        pub fn get_employee_risk_tier(env: Env, employee: Address) -> u32 {
+            env.storage().persistent().get(&(TIER, employee)).unwrap_or(3)
+        }
+
+        pub fn set_disputed(env: Env, employee: Address, disputed: bool) {
+            env.storage().persistent().set(&(DISPUTED, employee), &disputed);
+        }
+
+        pub fn is_profile_disputed(env: Env, employee: Address) -> bool {
+            env.storage().persistent().get(&(DISPUTED, employee)).unwrap_or(false)
+        }
+    }
+}
+
+/// Registers a mock streaming contract holding a 100,000 stream for the borrower
+fn register_streaming<'a>(
+    env: &'a Env,
+    client: &LendingContractClient,
+    admin: &Address,
+    borrower: &Address,
+    stream_id: u32,
+) -> mock_streaming::MockStreamingContractClient<'a> {
+    let streaming_id = env.register(mock_streaming::MockStreamingContract, ());
+    let streaming = mock_streaming::MockStreamingContractClient::new(env, &streaming_id);
+    client.set_streaming_contract(admin, &streaming_id);
+    streaming.set_stream(&stream_id, borrower, &100_000i128);
+    streaming
+}
+
+/// Creates a loan product open to all tiers at the plain tier rate
+fn register_product(env: &Env, client: &LendingContractClient, admin: &Address) -> LoanTerms {
+    let product = LoanProduct {
+        id: 0,
+        name: String::from_str(env, "Salary loan"),
+        min_amount: 1,
+        max_amount: 1_000_000,
+        term_options: vec![env, 12u32],
+        rate_spreads: vec![env, 0u32, 0, 0, 0, 0],
+        origination_fee_bps: None,
+        eligible_tiers: vec![env, 1u32, 2, 3, 4, 5],
+        is_active: true,
+    };
+    let product_id = client.create_loan_product(admin, &product);
+    LoanTerms { product_id, term_months: 12 }
+}
 
 #[test]
 fn test_request_loan() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, LendingContract);
+    let contract_id = env.register(LendingContract, ());
     let client = LendingContractClient::new(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let borrower = Address::generate(&env);
     let amount = 1000i128;
-    let risk_tier = 3u8;
+    let risk_tier = 3u32;
     let stream_id = 1u32;
 
     env.mock_all_auths();
 
-    let loan_id = client.request_loan(&borrower, &amount, &risk_tier, &stream_id);
+    let terms = register_product(&env, &client, &admin);
+    
+    // Loans need the streaming contract to value the collateral
+    assert!(client.try_request_loan(&borrower, &amount, &risk_tier, &stream_id, &None, &terms).is_err());
+    let streaming = register_streaming(&env, &client, &admin, &borrower, stream_id);
+    
+    // Someone else's stream can't be pledged, even with the borrower's own stream backing the limit
+    let other = Address::generate(&env);
+    streaming.set_stream(&2u32, &other, &100_000i128);
+    assert!(client.try_request_loan(&borrower, &amount, &risk_tier, &2u32, &None, &terms).is_err());
+    assert!(client.try_open_credit_line(&borrower, &amount, &risk_tier, &2u32).is_err());
+
+    let loan_id = client.request_loan(&borrower, &amount, &risk_tier, &stream_id, &None, &terms);
     
     assert_eq!(loan_id, 1);
     
@@ -33,23 +236,30 @@ fn test_request_loan() {
 #[test]
 fn test_approve_and_repay_loan() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, LendingContract);
+    let contract_id = env.register(LendingContract, ());
     let client = LendingContractClient::new(&env, &contract_id);
 
-    client.initialize();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
 
     let borrower = Address::generate(&env);
-    let admin = Address::generate(&env);
 
     env.mock_all_auths();
 
-    let loan_id = client.request_loan(&borrower, &1000i128, &2u8, &1u32);
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &1000i128, &2u32, &1u32, &None, &terms);
     
     // Approve loan
     client.approve_loan(&admin, &loan_id);
     let loan = client.get_loan(&loan_id);
     assert!(matches!(loan.status, LoanStatus::Approved));
     
+    // Repayments must be positive
+    assert!(client.try_repay_loan(&borrower, &loan_id, &-500i128).is_err());
+    assert!(client.try_repay_loan(&borrower, &loan_id, &0i128).is_err());
+    
     // Repay partial amount
     let payment = client.repay_loan(&borrower, &loan_id, &500i128);
     assert_eq!(payment, 500i128);
@@ -64,13 +274,1046 @@ fn test_approve_and_repay_loan() {
     assert!(matches!(loan.status, LoanStatus::Repaid));
 }
 
+#[test]
+fn test_small_repayments_accrue_interest() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    // 60,000 at 4.5% earns one unit of interest roughly every three hours
+    let loan_id = client.request_loan(&borrower, &60000i128, &2u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    
+    // Repaying every hour still accrues interest over the whole day (about 7.4 units)
+    for hour in 1..=24u64 {
+        env.ledger().with_mut(|li| li.timestamp = hour * 60 * 60);
+        client.repay_loan(&borrower, &loan_id, &1i128);
+    }
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.interest_paid + loan.accrued_interest, 7i128);
+    assert!(loan.last_accrual < 24 * 60 * 60);
+}
+
 #[test]
 fn test_max_loan_percentage() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, LendingContract);
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_max_loan_percentage(&1u32), 80u32);
+    assert_eq!(client.get_max_loan_percentage(&3u32), 50u32);
+    assert_eq!(client.get_max_loan_percentage(&5u32), 25u32);
+}
+
+#[test]
+fn test_refinance_loan() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
     let client = LendingContractClient::new(&env, &contract_id);
 
-    assert_eq!(client.get_max_loan_percentage(&1u8), 80u32);
-    assert_eq!(client.get_max_loan_percentage(&3u8), 50u32);
-    assert_eq!(client.get_max_loan_percentage(&5u8), 25u32);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &1000i128, &4u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    client.repay_loan(&borrower, &loan_id, &400i128);
+    
+    // Borrower improved to tier 2 on their work profile, which overrides the tier passed in
+    let work_profile_id = env.register(mock_work_profile::MockWorkProfileContract, ());
+    let work_profile = mock_work_profile::MockWorkProfileContractClient::new(&env, &work_profile_id);
+    client.set_work_profile_contract(&admin, &work_profile_id);
+    work_profile.set_tier(&borrower, &2u32);
+    
+    // Disputed profiles can't be repriced
+    work_profile.set_disputed(&borrower, &true);
+    assert!(client.try_refinance_loan(&admin, &loan_id, &2u32).is_err());
+    work_profile.set_disputed(&borrower, &false);
+    
+    // Refinance remaining balance
+    let new_loan_id = client.refinance_loan(&admin, &loan_id, &1u32);
+    assert_eq!(new_loan_id, 2);
+    
+    let old_loan = client.get_loan(&loan_id);
+    assert!(matches!(old_loan.status, LoanStatus::Refinanced));
+    
+    let new_loan = client.get_loan(&new_loan_id);
+    assert!(matches!(new_loan.status, LoanStatus::Approved));
+    assert_eq!(new_loan.amount, 600i128);
+    assert_eq!(new_loan.risk_tier, 2u32);
+    assert_eq!(new_loan.interest_rate, 450u32); // 4.5% for tier 2
+    assert_eq!(new_loan.previous_loan_id, Some(loan_id));
+    
+    // Both loans remain queryable through the chain
+    let history = client.get_loan_history(&new_loan_id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().id, loan_id);
+    assert_eq!(history.get(1).unwrap().id, new_loan_id);
+    assert_eq!(client.get_loan_transactions(&loan_id).len(), 4);
+    
+    let summary = client.get_loan_summary();
+    assert_eq!(summary.refinanced_loans, 1);
+    assert_eq!(summary.total_outstanding, 600i128);
+    
+    // Only the repaid part stays with the old loan's tier, the rest counts once under the new tier
+    let tiers = client.get_portfolio_breakdown();
+    let tier4 = tiers.get(3).unwrap();
+    assert_eq!(tier4.principal, 400i128);
+    assert_eq!(tier4.outstanding, 0i128);
+    assert_eq!(tier4.weighted_average_rate, 550u32);
+    let tier2 = tiers.get(1).unwrap();
+    assert_eq!(tier2.principal, 600i128);
+    assert_eq!(tier2.outstanding, 600i128);
+    let cohorts = client.get_cohort_breakdown();
+    assert_eq!(cohorts.get(0).unwrap().principal, 1000i128);
+}
+
+#[test]
+fn test_restructure_loan() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &1200i128, &3u32, &1u32, &None, &terms);
+    
+    // Approved two months after the request, so no months of the term have elapsed yet
+    env.ledger().with_mut(|li| li.timestamp = 2 * 30 * 24 * 60 * 60);
+    client.approve_loan(&admin, &loan_id);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.term_months, 12);
+    assert_eq!(loan.installment_amount, 100i128);
+    
+    // Extend to 24 months, halving the installment
+    let installment = client.restructure_loan(&admin, &loan_id, &24u32);
+    assert_eq!(installment, 50i128);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.term_months, 24);
+    assert_eq!(loan.installment_amount, 50i128);
+    
+    let transactions = client.get_loan_transactions(&loan_id);
+    let last = transactions.get(transactions.len() - 1).unwrap();
+    assert!(matches!(last.transaction_type, TransactionType::Restructure));
+}
+
+#[test]
+fn test_guarantor_liability() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let guarantor = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    streaming.set_stream(&7u32, &guarantor, &100_000i128);
+    let terms = register_product(&env, &client, &admin);
+
+    // Pledged stream must exist and belong to the guarantor
+    for stream_id in [999u32, 1] {
+        let pledge = GuarantorPledge {
+            guarantor: guarantor.clone(),
+            collateral: GuarantorCollateral::Stream(stream_id),
+        };
+        assert!(client.try_request_loan(&borrower, &1000i128, &5u32, &1u32, &Some(pledge), &terms).is_err());
+    }
+
+    let pledge = GuarantorPledge {
+        guarantor: guarantor.clone(),
+        collateral: GuarantorCollateral::Stream(7u32),
+    };
+    let loan_id = client.request_loan(&borrower, &1000i128, &5u32, &1u32, &Some(pledge), &terms);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.guarantor, Some(guarantor.clone()));
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 1000i128);
+    
+    client.approve_loan(&admin, &loan_id);
+    client.repay_loan(&borrower, &loan_id, &300i128);
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 700i128);
+    
+    // Guarantor is not liable before default
+    assert!(client.try_guarantor_repay(&guarantor, &loan_id, &100i128).is_err());
+    
+    // Default after a year with 42 unpaid interest (6% of 700)
+    env.ledger().with_mut(|li| li.timestamp = 365 * 24 * 60 * 60);
+    client.mark_default(&admin, &loan_id);
+    let guarantee = client.get_guarantee(&loan_id).unwrap();
+    assert!(guarantee.liable);
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 742i128);
+    
+    // Guarantor's pledged stream is liened alongside the borrower's
+    assert_eq!(streaming.get_lien(&1u32), 742i128);
+    assert_eq!(streaming.get_lien(&7u32), 742i128);
+    
+    // Borrower's stream has nothing accrued, so recovery falls to the guarantor's stream, interest first
+    streaming.set_available(&7u32, &200i128);
+    assert_eq!(client.recover_from_stream(&loan_id), 200i128);
+    assert_eq!(client.get_loan(&loan_id).interest_paid, 42i128);
+    assert_eq!(client.get_guarantee(&loan_id).unwrap().paid_amount, 200i128);
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 542i128);
+    
+    // Repayments must be positive
+    assert!(client.try_guarantor_repay(&guarantor, &loan_id, &-500i128).is_err());
+    
+    // Recovery shrank the borrower's lien to what is still owed
+    assert_eq!(streaming.get_lien(&1u32), 542i128);
+    assert_eq!(streaming.get_lien(&7u32), 542i128);
+    
+    // A partial guarantor payment shrinks both liens
+    assert_eq!(client.guarantor_repay(&guarantor, &loan_id, &300i128), 300i128);
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 242i128);
+    assert_eq!(streaming.get_lien(&1u32), 242i128);
+    assert_eq!(streaming.get_lien(&7u32), 242i128);
+    
+    // Borrower's withdrawal pays only the remaining balance and the rest stays with the borrower
+    streaming.set_available(&1u32, &1000i128);
+    assert_eq!(streaming.withdraw(&contract_id, &1u32), 242i128);
+    assert_eq!(streaming.calculate_available(&1u32), 758i128);
+    assert!(matches!(client.get_loan(&loan_id).status, LoanStatus::Defaulted));
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 0i128);
+    
+    // Guarantor's stale lien claims nothing more
+    streaming.set_available(&7u32, &1000i128);
+    assert_eq!(streaming.withdraw(&contract_id, &7u32), 0i128);
+    assert_eq!(streaming.get_lien(&7u32), 0i128);
+    assert_eq!(client.get_guarantee(&loan_id).unwrap().paid_amount, 500i128);
+}
+
+#[test]
+fn test_default_recovery_from_stream() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let stream_id = 3u32;
+
+    env.mock_all_auths();
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, stream_id);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &1000i128, &3u32, &stream_id, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    client.repay_loan(&borrower, &loan_id, &200i128);
+    
+    // Default after a year places a lien for the outstanding principal and 40 unpaid interest (5% of 800)
+    env.ledger().with_mut(|li| li.timestamp = 365 * 24 * 60 * 60);
+    client.mark_default(&admin, &loan_id);
+    assert_eq!(streaming.get_lien(&stream_id), 840i128);
+    
+    // Partial recovery from accrued wages, interest first
+    streaming.set_available(&stream_id, &500i128);
+    assert_eq!(client.recover_from_stream(&loan_id), 500i128);
+    assert_eq!(client.get_loan(&loan_id).interest_paid, 40i128);
+    
+    // Remaining balance recovered, stream released
+    streaming.set_available(&stream_id, &1000i128);
+    assert_eq!(client.recover_from_stream(&loan_id), 340i128);
+    assert_eq!(streaming.get_lien(&stream_id), 0i128);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.recovered_amount, 840i128);
+    assert_eq!(loan.repaid_amount, 1000i128);
+    assert_eq!(loan.accrued_interest, 0i128);
+    
+    let transactions = client.get_loan_transactions(&loan_id);
+    let last = transactions.get(transactions.len() - 1).unwrap();
+    assert!(matches!(last.transaction_type, TransactionType::Recovery));
+    assert_eq!(last.amount, 340i128);
+    
+    let summary = client.get_loan_summary();
+    assert_eq!(summary.total_recovered, 840i128);
+}
+
+#[test]
+fn test_write_off_with_reserve() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    // Half of all interest paid goes to the reserve
+    client.set_reserve_rate(&admin, &5000u32);
+
+    let loan_id = client.request_loan(&borrower, &10000i128, &3u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    
+    // One year at 5% accrues 500 interest
+    env.ledger().with_mut(|li| li.timestamp = 365 * 24 * 60 * 60);
+    let payment = client.repay_loan(&borrower, &loan_id, &20000i128);
+    assert_eq!(payment, 10500i128);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.interest_paid, 500i128);
+    assert!(matches!(loan.status, LoanStatus::Repaid));
+    assert_eq!(client.get_reserve_balance(), 250i128);
+    
+    // Second loan, guaranteed with the guarantor's stream, defaults and is written off
+    let guarantor = Address::generate(&env);
+    streaming.set_stream(&2u32, &guarantor, &100_000i128);
+    let pledge = GuarantorPledge {
+        guarantor: guarantor.clone(),
+        collateral: GuarantorCollateral::Stream(2u32),
+    };
+    let loan_id = client.request_loan(&borrower, &1000i128, &3u32, &1u32, &Some(pledge), &terms);
+    client.approve_loan(&admin, &loan_id);
+    client.repay_loan(&borrower, &loan_id, &200i128);
+    
+    // Another year accrues 40 unpaid interest, written off with the principal
+    env.ledger().with_mut(|li| li.timestamp = 2 * 365 * 24 * 60 * 60);
+    client.mark_default(&admin, &loan_id);
+    
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 840i128);
+    
+    let loss = client.write_off(&admin, &loan_id);
+    assert_eq!(loss, 840i128);
+    
+    // Writing off releases the guarantor
+    assert!(!client.get_guarantee(&loan_id).unwrap().liable);
+    assert_eq!(client.get_guarantor_exposure(&guarantor), 0i128);
+    assert!(client.try_guarantor_repay(&guarantor, &loan_id, &1000i128).is_err());
+    
+    let loan = client.get_loan(&loan_id);
+    assert!(matches!(loan.status, LoanStatus::WrittenOff));
+    assert_eq!(loan.written_off_amount, 840i128);
+    
+    let summary = client.get_loan_summary();
+    assert_eq!(summary.written_off_loans, 1);
+    assert_eq!(summary.total_written_off, 840i128);
+    assert_eq!(summary.reserve_balance, 0i128);
+}
+
+#[test]
+fn test_protocol_fees() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    // 1% origination fee, 20% of interest to the protocol
+    client.set_fee_config(&admin, &100u32, &2000u32, &fee_recipient);
+    
+    // Only the stored admin can change fees or the streaming contract
+    let attacker = Address::generate(&env);
+    assert!(client.try_set_fee_config(&attacker, &100u32, &2000u32, &attacker).is_err());
+    assert!(client.try_set_streaming_contract(&attacker, &attacker).is_err());
+    assert!(client.try_initialize(&attacker).is_err());
+
+    let loan_id = client.request_loan(&borrower, &10000i128, &3u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(loan.origination_fee, 100i128);
+    assert_eq!(client.get_fee_balance(), 100i128);
+    
+    // One year at 5% accrues 500 interest, 100 of it to the protocol
+    env.ledger().with_mut(|li| li.timestamp = 365 * 24 * 60 * 60);
+    client.repay_loan(&borrower, &loan_id, &10500i128);
+    assert_eq!(client.get_fee_balance(), 200i128);
+    
+    let transactions = client.get_loan_transactions(&loan_id);
+    let fees = transactions.iter()
+        .filter(|tx| matches!(tx.transaction_type, TransactionType::OriginationFee | TransactionType::ProtocolFee))
+        .fold(0i128, |total, tx| total + tx.amount);
+    assert_eq!(fees, 200i128);
+    
+    // Only the fee recipient can claim
+    assert!(client.try_claim_fees(&admin).is_err());
+    assert_eq!(client.claim_fees(&fee_recipient), 200i128);
+    assert_eq!(client.get_fee_balance(), 0i128);
+}
+
+#[test]
+fn test_concurrent_loans_within_credit_limit() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    // Tier 3 may borrow 50% of the 100,000 stream
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let first = client.request_loan(&borrower, &30000i128, &3u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &first);
+    
+    // Top-up while the first loan is outstanding
+    let second = client.request_loan(&borrower, &15000i128, &3u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &second);
+    
+    let credit = client.get_credit_line(&borrower);
+    assert_eq!(credit.pledged_value, 100000i128);
+    assert_eq!(credit.limit, 50000i128);
+    assert_eq!(credit.used, 45000i128);
+    assert_eq!(credit.available, 5000i128);
+    
+    // Requests beyond the aggregate limit are refused
+    assert!(client.try_request_loan(&borrower, &6000i128, &3u32, &1u32, &None, &terms).is_err());
+    
+    // Repayment frees up credit
+    client.repay_loan(&borrower, &first, &10000i128);
+    assert_eq!(client.get_credit_line(&borrower).available, 15000i128);
+    let third = client.request_loan(&borrower, &6000i128, &3u32, &1u32, &None, &terms);
+    
+    // A line opened while the request is pending reserves its limit, and approval re-checks it
+    let line_id = client.open_credit_line(&borrower, &9000i128, &3u32, &1u32);
+    streaming.set_stream(&1u32, &borrower, &90_000i128);
+    assert!(client.try_approve_loan(&admin, &third).is_err());
+    client.close_credit_line(&borrower, &line_id);
+    client.approve_loan(&admin, &third);
+}
+
+#[test]
+fn test_revolving_credit_line() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+
+    // Tier 1 (80%) line at 4% APR
+    let line_id = client.open_credit_line(&borrower, &36500i128, &1u32, &1u32);
+    assert_eq!(client.get_credit_line(&borrower).used, 36500i128);
+    
+    // Lines can't be drawn until approved
+    assert!(client.try_draw(&borrower, &line_id, &1i128).is_err());
+    client.approve_credit_line(&admin, &line_id);
+    
+    client.draw(&borrower, &line_id, &36500i128);
+    assert!(client.try_draw(&borrower, &line_id, &1i128).is_err());
+    
+    // Interest accrues per full day: 36,500 * 4% / 365 = 4 per day
+    env.ledger().with_mut(|li| li.timestamp = 10 * 24 * 60 * 60 + 100);
+    let line = client.get_line_state(&line_id);
+    assert_eq!(line.accrued_interest, 40i128);
+    
+    // Repayments must be positive
+    assert!(client.try_repay_line(&borrower, &line_id, &-10_000i128).is_err());
+    
+    // Repay interest and part of the principal, then draw again
+    assert_eq!(client.repay_line(&borrower, &line_id, &10040i128), 10040i128);
+    let line = client.get_line_state(&line_id);
+    assert_eq!(line.drawn, 26500i128);
+    assert_eq!(line.interest_paid, 40i128);
+    client.draw(&borrower, &line_id, &5000i128);
+    
+    let transactions = client.get_loan_transactions(&line_id);
+    assert_eq!(transactions.len(), 3);
+    assert!(matches!(transactions.get(1).unwrap().transaction_type, TransactionType::LineRepayment));
+    
+    // Closing requires a zero balance
+    assert!(client.try_close_credit_line(&borrower, &line_id).is_err());
+    client.repay_line(&borrower, &line_id, &31500i128);
+    client.close_credit_line(&borrower, &line_id);
+    assert_eq!(client.get_credit_line(&borrower).used, 0i128);
+}
+
+#[test]
+fn test_credit_line_tier_interest_and_exposure() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+    let work_profile_id = env.register(mock_work_profile::MockWorkProfileContract, ());
+    let work_profile = mock_work_profile::MockWorkProfileContractClient::new(&env, &work_profile_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let employer = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    streaming.set_employer(&1u32, &employer);
+    client.set_work_profile_contract(&admin, &work_profile_id);
+    client.set_max_employer_exposure(&admin, &5000i128);
+    
+    // The work profile tier overrides the tier the borrower asks for
+    work_profile.set_tier(&borrower, &2u32);
+    let line_id = client.open_credit_line(&borrower, &10000i128, &1u32, &1u32);
+    let line = client.get_line_state(&line_id);
+    assert_eq!(line.risk_tier, 2u32);
+    assert_eq!(line.interest_rate, 450u32);
+    client.approve_credit_line(&admin, &line_id);
+    
+    // Draws count against the employer's exposure
+    client.draw(&borrower, &line_id, &4000i128);
+    assert_eq!(client.get_employer_exposure(&employer), 4000i128);
+    assert!(client.try_draw(&borrower, &line_id, &1001i128).is_err());
+    
+    // Daily interest on 4,000 at 4.5% rounds to zero but carries until a whole unit is earned
+    env.ledger().with_mut(|li| li.timestamp = 24 * 60 * 60 + 10);
+    assert_eq!(client.get_line_state(&line_id).accrued_interest, 0i128);
+    env.ledger().with_mut(|li| li.timestamp = 3 * 24 * 60 * 60 + 10);
+    assert_eq!(client.get_line_state(&line_id).accrued_interest, 1i128);
+    
+    // Repaid principal frees exposure
+    client.repay_line(&borrower, &line_id, &1001i128);
+    assert_eq!(client.get_employer_exposure(&employer), 3000i128);
+    
+    // The credit limit follows the current work profile tier
+    assert_eq!(client.get_credit_line(&borrower).risk_tier, 2u32);
+    work_profile.set_tier(&borrower, &4u32);
+    let credit = client.get_credit_line(&borrower);
+    assert_eq!(credit.risk_tier, 4u32);
+    assert_eq!(credit.limit, 35000i128);
+}
+
+#[test]
+fn test_earned_wage_advance() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    streaming.set_available(&1u32, &1000i128);
+    client.set_advance_fee(&admin, &100u32); // 1%
+
+    // Only the stream's employee can take an advance
+    assert!(client.try_request_advance(&other, &1u32, &500i128).is_err());
+    
+    let advance_id = client.request_advance(&borrower, &1u32, &500i128);
+    let advance = client.get_advance(&advance_id);
+    assert_eq!(advance.fee, 5i128);
+    assert_eq!(streaming.get_lien(&1u32), 505i128);
+    
+    // Advances are bounded by the accrued balance not already advanced
+    assert!(client.try_request_advance(&borrower, &1u32, &500i128).is_err());
+    
+    // Employee's next withdrawal repays the advance through the lien
+    client.on_lien_collected(&1u32, &borrower, &300i128);
+    let advance = client.get_advance(&advance_id);
+    assert_eq!(advance.repaid_amount, 300i128);
+    assert!(!advance.is_repaid);
+    
+    client.on_lien_collected(&1u32, &borrower, &205i128);
+    let advance = client.get_advance(&advance_id);
+    assert!(advance.is_repaid);
+    
+    let transactions = client.get_loan_transactions(&advance_id);
+    assert_eq!(transactions.len(), 3);
+    assert!(matches!(transactions.get(0).unwrap().transaction_type, TransactionType::Advance));
+    assert!(matches!(transactions.get(2).unwrap().transaction_type, TransactionType::AdvanceRepayment));
+}
+
+#[test]
+fn test_loan_position_transfer() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let treasury = admin.clone(); // Approving admin funds the loan
+    let partner = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &1000i128, &3u32, &1u32, &None, &terms);
+    client.approve_loan(&treasury, &loan_id);
+    
+    // Funder holds the whole position
+    let positions = client.get_position_owner(&loan_id);
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions.get(0).unwrap().owner, treasury);
+    
+    // Sell 40% to a partner
+    client.transfer_position(&treasury, &partner, &loan_id, &4000u32);
+    assert!(client.try_transfer_position(&partner, &treasury, &loan_id, &5000u32).is_err());
+    
+    // Repayments are routed pro rata
+    client.repay_loan(&borrower, &loan_id, &500i128);
+    assert_eq!(client.get_position_proceeds(&treasury), 300i128);
+    assert_eq!(client.get_position_proceeds(&partner), 200i128);
+    
+    // Sell the rest, later repayments go only to the partner
+    client.transfer_position(&treasury, &partner, &loan_id, &6000u32);
+    let positions = client.get_position_owner(&loan_id);
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions.get(0).unwrap().share_bps, 10000u32);
+    
+    client.repay_loan(&borrower, &loan_id, &500i128);
+    assert_eq!(client.claim_position_proceeds(&partner), 700i128);
+    let claims = client.get_loan_transactions(&0u32);
+    let claim = claims.get(claims.len() - 1).unwrap();
+    assert!(matches!(claim.transaction_type, TransactionType::PositionClaim));
+    assert_eq!(claim.amount, 700i128);
+    assert_eq!(client.get_position_proceeds(&partner), 0i128);
+    assert_eq!(client.get_position_proceeds(&treasury), 300i128);
+}
+
+#[test]
+fn test_portfolio_breakdown() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    // March 2024 cohort: two tier 2 loans, one defaults after partial repayment
+    env.ledger().with_mut(|li| li.timestamp = 1_710_460_800); // 2024-03-15
+    let first = client.request_loan(&borrower, &1000i128, &2u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &first);
+    let second = client.request_loan(&borrower, &3000i128, &2u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &second);
+    client.repay_loan(&borrower, &first, &1000i128);
+    client.repay_loan(&borrower, &second, &500i128);
+    client.mark_default(&admin, &second);
+    
+    // April 2024 cohort: one tier 4 loan
+    env.ledger().with_mut(|li| li.timestamp = 1_712_016_000); // 2024-04-02
+    let third = client.request_loan(&borrower, &2000i128, &4u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &third);
+    
+    let tiers = client.get_portfolio_breakdown();
+    assert_eq!(tiers.len(), 5);
+    let tier2 = tiers.get(1).unwrap();
+    assert_eq!(tier2.key, 2);
+    assert_eq!(tier2.loans, 2);
+    assert_eq!(tier2.principal, 4000i128);
+    assert_eq!(tier2.outstanding, 0i128);
+    assert_eq!(tier2.repaid, 1500i128);
+    assert_eq!(tier2.defaulted, 1);
+    assert_eq!(tier2.default_rate, 5000u32);
+    assert_eq!(tier2.weighted_average_rate, 450u32);
+    let tier4 = tiers.get(3).unwrap();
+    assert_eq!(tier4.outstanding, 2000i128);
+    assert_eq!(tier4.weighted_average_rate, 550u32);
+    
+    let cohorts = client.get_cohort_breakdown();
+    assert_eq!(cohorts.len(), 2);
+    assert_eq!(cohorts.get(0).unwrap().key, 202403u32);
+    assert_eq!(cohorts.get(0).unwrap().loans, 2);
+    assert_eq!(cohorts.get(1).unwrap().key, 202404u32);
+    assert_eq!(cohorts.get(1).unwrap().principal, 2000i128);
+}
+
+#[test]
+fn test_employer_exposure_limit() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+    let coworker = Address::generate(&env);
+    let employer = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    // Both borrowers are paid by the same employer
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+    streaming.set_stream(&2u32, &coworker, &100_000i128);
+    streaming.set_employer(&1u32, &employer);
+    streaming.set_employer(&2u32, &employer);
+    client.set_max_employer_exposure(&admin, &5000i128);
+
+    let loan_id = client.request_loan(&borrower, &3000i128, &1u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    assert_eq!(client.get_employer_exposure(&employer), 3000i128);
+    
+    // Coworker's request would breach the employer limit
+    let result = client.try_request_loan(&coworker, &2500i128, &1u32, &2u32, &None, &terms);
+    assert_eq!(result.err().unwrap().unwrap(), LendingError::EmployerExposureExceeded.into());
+    
+    // Repayment frees up employer capacity
+    client.repay_loan(&borrower, &loan_id, &1000i128);
+    assert_eq!(client.get_employer_exposure(&employer), 2000i128);
+    let loan_id = client.request_loan(&coworker, &2500i128, &1u32, &2u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    assert_eq!(client.get_employer_exposure(&employer), 4500i128);
+    
+    // Wage advances count against the same limit until collected
+    streaming.set_available(&2u32, &1000i128);
+    let result = client.try_request_advance(&coworker, &2u32, &600i128);
+    assert_eq!(result.err().unwrap().unwrap(), LendingError::EmployerExposureExceeded.into());
+    client.request_advance(&coworker, &2u32, &500i128);
+    assert_eq!(client.get_employer_exposure(&employer), 5000i128);
+    client.on_lien_collected(&2u32, &coworker, &500i128);
+    assert_eq!(client.get_employer_exposure(&employer), 4500i128);
+}
+
+#[test]
+fn test_employment_ended_moves_loans_at_risk() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    // Without a streaming contract there is nothing to accelerate and ending a stream must not fail
+    client.on_employment_ended(&borrower, &borrower, &Some(1u32));
+    assert!(!client.is_borrower_at_risk(&borrower));
+
+    let streaming = register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+    client.set_acceleration_rate(&admin, &5000u32);
+
+    // Second job pays another stream from another employer
+    let second_employer = Address::generate(&env);
+    streaming.set_stream(&2u32, &borrower, &100_000i128);
+    streaming.set_employer(&2u32, &second_employer);
+
+    let loan_id = client.request_loan(&borrower, &2000i128, &1u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    let second_id = client.request_loan(&borrower, &1200i128, &1u32, &2u32, &None, &terms);
+    client.approve_loan(&admin, &second_id);
+    
+    // Streaming contract signals the first stream ended
+    client.on_employment_ended(&borrower, &borrower, &Some(1u32));
+    let loan = client.get_loan(&loan_id);
+    assert!(matches!(loan.status, LoanStatus::AtRisk));
+    assert_eq!(loan.installment_amount, 1000i128); // Half the remaining balance is due now
+    assert!(client.is_borrower_at_risk(&borrower));
+    assert_eq!(client.get_loan_summary().at_risk_loans, 1);
+    
+    // The loan backed by the other job is not accelerated
+    let second = client.get_loan(&second_id);
+    assert!(matches!(second.status, LoanStatus::Approved));
+    assert_eq!(second.installment_amount, 100i128);
+    
+    // Deactivation by the second employer reaches loans on that employer's streams
+    client.on_employment_ended(&borrower, &second_employer, &None);
+    assert!(matches!(client.get_loan(&second_id).status, LoanStatus::AtRisk));
+    assert_eq!(client.get_loan_summary().at_risk_loans, 2);
+    
+    // New credit is blocked but repayments continue
+    assert!(client.try_request_loan(&borrower, &500i128, &1u32, &1u32, &None, &terms).is_err());
+    client.repay_loan(&borrower, &loan_id, &167i128);
+    
+    // Paying the accelerated installment a month in is on time against the regular schedule
+    let work_profile_id = env.register(mock_work_profile::MockWorkProfileContract, ());
+    let work_profile = mock_work_profile::MockWorkProfileContractClient::new(&env, &work_profile_id);
+    client.set_work_profile_contract(&admin, &work_profile_id);
+    env.ledger().with_mut(|li| li.timestamp = 30 * 24 * 60 * 60);
+    client.repay_loan(&borrower, &loan_id, &1000i128);
+    let events = work_profile.get_events();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events.get(0).unwrap(), CreditEvent::OnTimePayment));
+    
+    // Admin clears the block and the loan returns to its regular installments
+    client.clear_at_risk(&admin, &borrower);
+    assert!(!client.is_borrower_at_risk(&borrower));
+    let loan = client.get_loan(&loan_id);
+    assert!(matches!(loan.status, LoanStatus::Approved));
+    assert_eq!(loan.installment_amount, 167i128);
+    assert_eq!(loan.scheduled_installment, None);
+    assert_eq!(client.get_loan(&second_id).installment_amount, 100i128);
+    client.request_loan(&borrower, &500i128, &1u32, &1u32, &None, &terms);
+    
+    // A regular installment five months in is on schedule, not late against the accelerated amount
+    env.ledger().with_mut(|li| li.timestamp = 5 * 30 * 24 * 60 * 60);
+    client.repay_loan(&borrower, &loan_id, &167i128);
+    let events = work_profile.get_events();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events.get(1).unwrap(), CreditEvent::OnTimePayment));
+}
+
+#[test]
+fn test_loan_statement() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+
+    let loan_id = client.request_loan(&borrower, &10_000i128, &1u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &loan_id);
+    
+    // Repay after half a year, then take a second loan
+    let half_year = 15_768_000u64;
+    env.ledger().with_mut(|li| li.timestamp = half_year);
+    client.repay_loan(&borrower, &loan_id, &2000i128);
+    let interest_paid = client.get_loan(&loan_id).interest_paid;
+    assert!(interest_paid > 0);
+    let second_id = client.request_loan(&borrower, &1000i128, &1u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &second_id);
+    
+    // First period covers the disbursement and the interest earned up to the repayment
+    let statement = client.get_loan_statement(&loan_id, &0u64, &(half_year - 1));
+    assert_eq!(statement.opening_balance, 0i128);
+    assert_eq!(statement.disbursed, 10_000i128);
+    assert_eq!(statement.payments, 0i128);
+    
+    let statement = client.get_loan_statement(&loan_id, &half_year, &half_year);
+    assert_eq!(statement.opening_balance, 10_000i128 + interest_paid);
+    assert_eq!(statement.payments, 2000i128);
+    assert_eq!(statement.closing_balance, 8000i128 + interest_paid); // Interest was paid first
+    
+    // Replayed accrual matches the live loan
+    env.ledger().with_mut(|li| li.timestamp = 2 * half_year);
+    let statement = client.get_loan_statement(&loan_id, &(half_year + 1), &(2 * half_year));
+    client.repay_loan(&borrower, &loan_id, &1i128);
+    let loan = client.get_loan(&loan_id);
+    assert_eq!(statement.closing_balance, loan.amount - loan.repaid_amount + loan.accrued_interest + 1);
+    assert_eq!(
+        statement.closing_balance,
+        statement.opening_balance + statement.interest_accrued - statement.payments
+    );
+    
+    // Borrower statement aggregates both loans
+    let borrower_statement = client.get_borrower_statement(&borrower, &0u64, &half_year);
+    assert_eq!(borrower_statement.loans.len(), 2);
+    assert_eq!(borrower_statement.disbursed, 11_000i128);
+    assert_eq!(borrower_statement.payments, 2000i128);
+    assert_eq!(borrower_statement.closing_balance, 9000i128 + interest_paid);
+    
+    // Refinancing moves the balance between loans without disbursing anything
+    let refinanced_id = client.refinance_loan(&admin, &second_id, &1u32);
+    let outstanding = client.get_loan(&refinanced_id).amount;
+    let statement = client.get_loan_statement(&refinanced_id, &(2 * half_year), &(2 * half_year));
+    assert_eq!(statement.disbursed, 0i128);
+    assert_eq!(statement.adjustments, -outstanding);
+    assert_eq!(statement.closing_balance, outstanding);
+    
+    let borrower_statement = client.get_borrower_statement(&borrower, &(2 * half_year), &(2 * half_year));
+    assert_eq!(borrower_statement.disbursed, 0i128);
+    assert_eq!(borrower_statement.adjustments, 0i128);
+}
+
+#[test]
+fn test_loan_product_catalog() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let standard = register_product(&env, &client, &admin);
+    
+    // Short-term product for strong tiers with a spread and its own fee
+    let product = LoanProduct {
+        id: 0,
+        name: String::from_str(&env, "Payday bridge"),
+        min_amount: 100,
+        max_amount: 2000,
+        term_options: vec![&env, 1u32, 3],
+        rate_spreads: vec![&env, 100u32, 200, 300, 400, 500],
+        origination_fee_bps: Some(200),
+        eligible_tiers: vec![&env, 1u32, 2],
+        is_active: true,
+    };
+    let bridge_id = client.create_loan_product(&admin, &product);
+    assert_eq!(client.get_loan_products().len(), 2);
+    
+    let terms = LoanTerms { product_id: bridge_id, term_months: 3 };
+    let loan_id = client.request_loan(&borrower, &1500i128, &2u32, &1u32, &None, &terms);
+    let loan = client.get_loan(&loan_id);
+    let standard_loan = client.get_loan(&client.request_loan(&borrower, &1500i128, &2u32, &1u32, &None, &standard));
+    assert_eq!(loan.interest_rate, standard_loan.interest_rate + 200);
+    assert_eq!(loan.installment_amount, 500i128);
+    
+    // Product fee schedule applies at approval
+    client.approve_loan(&admin, &loan_id);
+    assert_eq!(client.get_loan(&loan_id).origination_fee, 30i128);
+    
+    // Requests outside the product's limits, terms or tiers are rejected
+    assert!(client.try_request_loan(&borrower, &5000i128, &2u32, &1u32, &None, &terms).is_err());
+    assert!(client.try_request_loan(&borrower, &500i128, &4u32, &1u32, &None, &terms).is_err());
+    let long_terms = LoanTerms { product_id: bridge_id, term_months: 12 };
+    assert!(client.try_request_loan(&borrower, &500i128, &2u32, &1u32, &None, &long_terms).is_err());
+    
+    // Retired products disappear from the catalog
+    client.set_loan_product_active(&admin, &bridge_id, &false);
+    assert_eq!(client.get_loan_products().len(), 1);
+    assert!(client.try_request_loan(&borrower, &500i128, &2u32, &1u32, &None, &terms).is_err());
+}
+
+#[test]
+fn test_credit_events_reported_to_work_profile() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+    let work_profile_id = env.register(mock_work_profile::MockWorkProfileContract, ());
+    let work_profile = mock_work_profile::MockWorkProfileContractClient::new(&env, &work_profile_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+    client.set_work_profile_contract(&admin, &work_profile_id);
+    work_profile.set_tier(&borrower, &1u32);
+
+    // Disputed profiles can't be priced until the dispute is resolved
+    work_profile.set_disputed(&borrower, &true);
+    assert!(client.try_request_loan(&borrower, &1200i128, &1u32, &1u32, &None, &terms).is_err());
+    work_profile.set_disputed(&borrower, &false);
+    
+    // The tier is read from the work profile, not the request
+    let loan_id = client.request_loan(&borrower, &1200i128, &5u32, &1u32, &None, &terms);
+    assert_eq!(client.get_loan(&loan_id).risk_tier, 1u32);
+    client.approve_loan(&admin, &loan_id);
+    
+    // First installment paid within the month
+    env.ledger().with_mut(|li| li.timestamp = 20 * 24 * 60 * 60);
+    client.repay_loan(&borrower, &loan_id, &100i128);
+    
+    // Dust and further payments in the same period are not reported again
+    client.repay_loan(&borrower, &loan_id, &1i128);
+    client.repay_loan(&borrower, &loan_id, &100i128);
+    assert_eq!(work_profile.get_events().len(), 1);
+    
+    // Four months in, only one installment has been paid
+    env.ledger().with_mut(|li| li.timestamp = 4 * 30 * 24 * 60 * 60);
+    client.repay_loan(&borrower, &loan_id, &100i128);
+    
+    // Paying off the balance is reported as a payoff
+    client.repay_loan(&borrower, &loan_id, &2000i128);
+    
+    let defaulted = client.request_loan(&borrower, &500i128, &1u32, &1u32, &None, &terms);
+    client.approve_loan(&admin, &defaulted);
+    client.mark_default(&admin, &defaulted);
+    
+    let events = work_profile.get_events();
+    assert_eq!(events.len(), 4);
+    assert!(matches!(events.get(0).unwrap(), CreditEvent::OnTimePayment));
+    assert!(matches!(events.get(1).unwrap(), CreditEvent::LatePayment));
+    assert!(matches!(events.get(2).unwrap(), CreditEvent::Payoff));
+    assert!(matches!(events.get(3).unwrap(), CreditEvent::Default));
+}
+
+#[test]
+fn test_require_verified_profile() {
+    let env = Env::default();
+    let contract_id = env.register(LendingContract, ());
+    let client = LendingContractClient::new(&env, &contract_id);
+    let work_profile_id = env.register(mock_work_profile::MockWorkProfileContract, ());
+    let work_profile = mock_work_profile::MockWorkProfileContractClient::new(&env, &work_profile_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let borrower = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    register_streaming(&env, &client, &admin, &borrower, 1);
+    let terms = register_product(&env, &client, &admin);
+    client.set_work_profile_contract(&admin, &work_profile_id);
+    client.set_require_verified_profile(&admin, &true);
+    
+    // Self-reported profiles are refused
+    assert!(client.try_request_loan(&borrower, &1000i128, &3u32, &1u32, &None, &terms).is_err());
+    
+    work_profile.set_verified(&borrower, &true);
+    client.request_loan(&borrower, &1000i128, &3u32, &1u32, &None, &terms);
 }
@@ -1,5 +1,12 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Vec, String};
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, Address, Env, Vec, String};
+
+/// Interface of the lending contract notified of lien collections and employment changes
+#[contractclient(name = "LendingClient")]
+pub trait LendingInterface {
+    fn on_lien_collected(env: Env, stream_id: u32, employee: Address, amount: i128);
+    fn on_employment_ended(env: Env, employee: Address, employer: Address, stream_id: Option<u32>);
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -16,6 +23,16 @@ pub struct SalaryStream {
     pub is_paused: bool,
 }
 
+// Interruptions of a stream by its employer
+#[derive(Clone)]
+#[contracttype]
+pub struct StreamPauses {
+    pub pause_count: u32,
+    pub paused_seconds: u64,      // Total time spent paused, excluding an ongoing pause
+    pub paused_at: Option<u64>,   // Start of the ongoing pause, if any
+    pub last_resumed_at: u64,     // 0 if never resumed
+}
+
 // Employee information structure
 #[derive(Clone)]
 #[contracttype]
@@ -33,6 +50,24 @@ pub struct EmployeeInfo {
     pub is_active: bool,
 }
 
+// One period of employment with an employer
+#[derive(Clone)]
+#[contracttype]
+pub struct EmploymentRecord {
+    pub employer: Address,
+    pub start_date: u64,
+    pub end_date: Option<u64>,  // None while the employment is ongoing
+}
+
+// Claim placed by the registered lending contract on a stream's future accruals
+#[derive(Clone)]
+#[contracttype]
+pub struct StreamLien {
+    pub lender: Address,
+    pub amount: i128,
+    pub collected: i128,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -43,6 +78,12 @@ pub enum DataKey {
     // Employee data storage keys
     EmployeeInfo(Address),
     EmployerEmployees(Address), // List of employee addresses for an employer
+    EmploymentHistory(Address), // Employment records of an employee across employers
+    // Lending integration
+    Admin,
+    LendingContract,
+    StreamLien(u32),
+    StreamPauses(u32),
 }
 
 #[contract]
@@ -50,49 +91,32 @@ pub struct SalaryStreamingContract;
 
 #[contractimpl]
 impl SalaryStreamingContract {
-    /// Register a new employee with name, email and phone
-    pub fn register_employee(
-        env: Env,
-        employer: Address,
-        employee: Address,
-        name: String,
-        email: String,
-        phone: String,
-        position: String,
-        department: String,
-        start_date: u64,
-    ) {
-        // Require authorization from employer
-        employer.require_auth();
-        
-        // Create employee info
-        let employee_info = EmployeeInfo {
-            address: employee.clone(),
-            name,
-            email,
-            phone,
-            position,
-            department,
-            employer: employer.clone(),
-            registration_time: env.ledger().timestamp(),
-            start_date,
-            end_date: None,  // No end date when registering
-            is_active: true,
-        };
-        
-        // Store employee info
-        env.storage().persistent().set(&DataKey::EmployeeInfo(employee.clone()), &employee_info);
-        
-        // Add to employer's employee list
-        let mut employer_employees: Vec<Address> = env.storage().persistent()
-            .get(&DataKey::EmployerEmployees(employer.clone()))
-            .unwrap_or_else(|| Vec::new(&env));
+    /// Initialize contract with an admin
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().persistent().has(&DataKey::Admin) {
+            panic!("Already initialized");
+        }
         
-        // Check if employee already exists in the list
-        if !employer_employees.iter().any(|addr| addr == employee) {
-            employer_employees.push_back(employee);
-            env.storage().persistent().set(&DataKey::EmployerEmployees(employer), &employer_employees);
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+    }
+    
+    /// Registers the lending contract allowed to place liens on streams (admin function)
+    pub fn set_lending_contract(env: Env, admin: Address, lending_contract: Address) {
+        admin.require_auth();
+        
+        let stored_admin: Address = env.storage().persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized");
+        if stored_admin != admin {
+            panic!("Unauthorized");
         }
+        
+        env.storage().persistent().set(&DataKey::LendingContract, &lending_contract);
+    }
+    
+    /// Gets the registered lending contract
+    pub fn get_lending_contract(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::LendingContract)
     }
     
     /// Get employee information
@@ -103,6 +127,7 @@ impl SalaryStreamingContract {
     }
     
     /// Update employee information (name, email and phone)
+    #[allow(clippy::too_many_arguments)] // Each profile field is its own argument
     pub fn update_employee_info(
         env: Env,
         employer: Address,
@@ -155,7 +180,17 @@ impl SalaryStreamingContract {
         // Deactivate and set end date
         employee_info.is_active = false;
         employee_info.end_date = Some(env.ledger().timestamp());
-        env.storage().persistent().set(&DataKey::EmployeeInfo(employee), &employee_info);
+        env.storage().persistent().set(&DataKey::EmployeeInfo(employee.clone()), &employee_info);
+        
+        // Close the current employment record
+        let mut history = Self::get_employment_history(env.clone(), employee.clone());
+        if let Some(mut last) = history.pop_back() {
+            last.end_date = employee_info.end_date;
+            history.push_back(last);
+            env.storage().persistent().set(&DataKey::EmploymentHistory(employee.clone()), &history);
+        }
+        
+        Self::notify_employment_ended(&env, &employee, &employer, None);
     }
     
     /// Reactivate an employee (rehire)
@@ -176,7 +211,23 @@ impl SalaryStreamingContract {
         // Reactivate and clear end date
         employee_info.is_active = true;
         employee_info.end_date = None;
-        env.storage().persistent().set(&DataKey::EmployeeInfo(employee), &employee_info);
+        env.storage().persistent().set(&DataKey::EmployeeInfo(employee.clone()), &employee_info);
+        
+        // Rehiring starts a new employment record with the same employer
+        let mut history = Self::get_employment_history(env.clone(), employee.clone());
+        history.push_back(EmploymentRecord {
+            employer,
+            start_date: env.ledger().timestamp(),
+            end_date: None,
+        });
+        env.storage().persistent().set(&DataKey::EmploymentHistory(employee), &history);
+    }
+    
+    /// Get an employee's employment records across employers, oldest first
+    pub fn get_employment_history(env: Env, employee: Address) -> Vec<EmploymentRecord> {
+        env.storage().persistent()
+            .get(&DataKey::EmploymentHistory(employee))
+            .unwrap_or_else(|| Vec::new(&env))
     }
 
     /// Update employee position and department
@@ -285,6 +336,14 @@ impl SalaryStreamingContract {
         // Require authorization from employer
         stream.employer.require_auth();
         
+        // Record the interruption once per pause
+        if !stream.is_paused {
+            let mut pauses = Self::get_stream_pauses(env.clone(), stream_id);
+            pauses.pause_count += 1;
+            pauses.paused_at = Some(env.ledger().timestamp());
+            env.storage().persistent().set(&DataKey::StreamPauses(stream_id), &pauses);
+        }
+        
         stream.is_paused = true;
         env.storage().persistent().set(&DataKey::Stream(stream_id), &stream);
     }
@@ -298,10 +357,31 @@ impl SalaryStreamingContract {
         // Require authorization from employer
         stream.employer.require_auth();
         
+        // Close out the ongoing pause
+        let mut pauses = Self::get_stream_pauses(env.clone(), stream_id);
+        if let Some(paused_at) = pauses.paused_at {
+            pauses.paused_seconds += env.ledger().timestamp().saturating_sub(paused_at);
+            pauses.paused_at = None;
+            pauses.last_resumed_at = env.ledger().timestamp();
+            env.storage().persistent().set(&DataKey::StreamPauses(stream_id), &pauses);
+        }
+        
         stream.is_paused = false;
         env.storage().persistent().set(&DataKey::Stream(stream_id), &stream);
     }
     
+    /// Gets the pause history of a stream
+    pub fn get_stream_pauses(env: Env, stream_id: u32) -> StreamPauses {
+        env.storage().persistent()
+            .get(&DataKey::StreamPauses(stream_id))
+            .unwrap_or(StreamPauses {
+                pause_count: 0,
+                paused_seconds: 0,
+                paused_at: None,
+                last_resumed_at: 0,
+            })
+    }
+    
     /// Ends a stream
     pub fn end_stream(env: Env, stream_id: u32) {
         let mut stream: SalaryStream = env.storage().persistent()
@@ -313,6 +393,8 @@ impl SalaryStreamingContract {
         
         stream.is_active = false;
         env.storage().persistent().set(&DataKey::Stream(stream_id), &stream);
+        
+        Self::notify_employment_ended(&env, &stream.employee, &stream.employer, Some(stream_id));
     }
     
     /// Calculates available balance for withdrawal
@@ -351,6 +433,15 @@ impl SalaryStreamingContract {
         // Require authorization from employee
         stream.employee.require_auth();
         
+        // Accruals claimed by a lien are paid to the lender first
+        if let Some(lien) = Self::get_stream_lien(env.clone(), stream_id) {
+            let collected = Self::settle_lien(&env, stream_id, i128::MAX);
+            if collected > 0 {
+                LendingClient::new(&env, &lien.lender).on_lien_collected(&stream_id, &stream.employee, &collected);
+                stream.withdrawn_amount += collected;
+            }
+        }
+        
         let available = Self::calculate_available(env.clone(), stream_id);
         
         // Check if requested amount is available
@@ -365,6 +456,74 @@ impl SalaryStreamingContract {
         amount
     }
     
+    /// Places a lien on a stream's accruals in favour of the registered lending contract
+    /// Adds to the existing lien amount if one is already in place.
+    pub fn place_lien(env: Env, lender: Address, stream_id: u32, amount: i128) {
+        // Require authorization from lender
+        lender.require_auth();
+        Self::require_lending_contract(&env, &lender);
+        
+        if amount <= 0 {
+            panic!("Lien amount must be positive");
+        }
+        
+        if !env.storage().persistent().has(&DataKey::Stream(stream_id)) {
+            panic!("Stream not found");
+        }
+        
+        let lien = match env.storage().persistent().get::<DataKey, StreamLien>(&DataKey::StreamLien(stream_id)) {
+            Some(mut lien) => {
+                lien.amount += amount;
+                lien
+            }
+            None => StreamLien {
+                lender,
+                amount,
+                collected: 0,
+            },
+        };
+        
+        env.storage().persistent().set(&DataKey::StreamLien(stream_id), &lien);
+    }
+    
+    /// Moves accrued funds claimed by a lien to the lender, up to `max_amount`
+    /// The lien is released once fully collected. Returns the amount collected.
+    pub fn collect_lien(env: Env, lender: Address, stream_id: u32, max_amount: i128) -> i128 {
+        // Require authorization from lender
+        lender.require_auth();
+        
+        let lien: StreamLien = env.storage().persistent()
+            .get(&DataKey::StreamLien(stream_id))
+            .expect("No lien on stream");
+        
+        if lien.lender != lender {
+            panic!("Unauthorized");
+        }
+        
+        Self::settle_lien(&env, stream_id, max_amount)
+    }
+    
+    /// Releases a lien, returning all future accruals to the employee
+    pub fn release_lien(env: Env, lender: Address, stream_id: u32) {
+        // Require authorization from lender
+        lender.require_auth();
+        
+        let lien: StreamLien = env.storage().persistent()
+            .get(&DataKey::StreamLien(stream_id))
+            .expect("No lien on stream");
+        
+        if lien.lender != lender {
+            panic!("Unauthorized");
+        }
+        
+        env.storage().persistent().remove(&DataKey::StreamLien(stream_id));
+    }
+    
+    /// Gets the lien on a stream, if any
+    pub fn get_stream_lien(env: Env, stream_id: u32) -> Option<StreamLien> {
+        env.storage().persistent().get(&DataKey::StreamLien(stream_id))
+    }
+    
     /// Gets stream details
     pub fn get_stream(env: Env, stream_id: u32) -> SalaryStream {
         env.storage().persistent()
@@ -422,6 +581,126 @@ impl SalaryStreamingContract {
         
         all_ids
     }
+    
+    /// Internal function to move accrued funds claimed by a stream's lien to the lender
+    /// Releases the lien once fully collected. Returns the amount collected.
+    fn settle_lien(env: &Env, stream_id: u32, max_amount: i128) -> i128 {
+        let mut lien: StreamLien = env.storage().persistent()
+            .get(&DataKey::StreamLien(stream_id))
+            .expect("No lien on stream");
+        
+        let mut stream: SalaryStream = env.storage().persistent()
+            .get(&DataKey::Stream(stream_id))
+            .expect("Stream not found");
+        
+        let available = Self::calculate_available(env.clone(), stream_id);
+        let amount = available.min(lien.amount - lien.collected).min(max_amount).max(0);
+        
+        // Collected funds count as withdrawn from the stream
+        stream.withdrawn_amount += amount;
+        lien.collected += amount;
+        env.storage().persistent().set(&DataKey::Stream(stream_id), &stream);
+        
+        if lien.collected >= lien.amount {
+            env.storage().persistent().remove(&DataKey::StreamLien(stream_id));
+        } else {
+            env.storage().persistent().set(&DataKey::StreamLien(stream_id), &lien);
+        }
+        
+        amount
+    }
+    
+    /// Internal function to notify the registered lending contract that an employee's income has stopped
+    /// `stream_id` is set when a single stream ended, otherwise all income from the employer stopped.
+    /// A failure in the lending contract never blocks ending a stream or deactivating an employee.
+    fn notify_employment_ended(env: &Env, employee: &Address, employer: &Address, stream_id: Option<u32>) {
+        if let Some(lending_contract) = Self::get_lending_contract(env.clone()) {
+            let _ = LendingClient::new(env, &lending_contract).try_on_employment_ended(employee, employer, &stream_id);
+        }
+    }
+    
+    /// Internal function to check that an address is the registered lending contract
+    fn require_lending_contract(env: &Env, lender: &Address) {
+        let lending_contract: Address = env.storage().persistent()
+            .get(&DataKey::LendingContract)
+            .expect("Lending contract not registered");
+        if lending_contract != *lender {
+            panic!("Unauthorized lender");
+        }
+    }
+}
+
+// Registration takes each profile field as its own argument. The SDK generates an argument builder
+// next to the contract impl, so the exemption from clippy's argument limit has to cover this module.
+#[allow(clippy::too_many_arguments)]
+mod registration {
+    use super::*;
+
+    #[contractimpl]
+    impl SalaryStreamingContract {
+        /// Register a new employee with name, email and phone
+        pub fn register_employee(
+            env: Env,
+            employer: Address,
+            employee: Address,
+            name: String,
+            email: String,
+            phone: String,
+            position: String,
+            department: String,
+            start_date: u64,
+        ) {
+            // Require authorization from employer
+            employer.require_auth();
+            
+            // Create employee info
+            let employee_info = EmployeeInfo {
+                address: employee.clone(),
+                name,
+                email,
+                phone,
+                position,
+                department,
+                employer: employer.clone(),
+                registration_time: env.ledger().timestamp(),
+                start_date,
+                end_date: None,  // No end date when registering
+                is_active: true,
+            };
+            
+            // Store employee info
+            env.storage().persistent().set(&DataKey::EmployeeInfo(employee.clone()), &employee_info);
+            
+            // Registering with a new employer ends the previous employment
+            let mut history = Self::get_employment_history(env.clone(), employee.clone());
+            if let Some(mut last) = history.last() {
+                if last.end_date.is_none() {
+                    history.pop_back();
+                    if last.employer != employer {
+                        last.end_date = Some(start_date);
+                        history.push_back(last);
+                    }
+                }
+            }
+            history.push_back(EmploymentRecord {
+                employer: employer.clone(),
+                start_date,
+                end_date: None,
+            });
+            env.storage().persistent().set(&DataKey::EmploymentHistory(employee.clone()), &history);
+            
+            // Add to employer's employee list
+            let mut employer_employees: Vec<Address> = env.storage().persistent()
+                .get(&DataKey::EmployerEmployees(employer.clone()))
+                .unwrap_or_else(|| Vec::new(&env));
+            
+            // Check if employee already exists in the list
+            if !employer_employees.iter().any(|addr| addr == employee) {
+                employer_employees.push_back(employee);
+                env.storage().persistent().set(&DataKey::EmployerEmployees(employer), &employer_employees);
+            }
+        }
+    }
 }
 
 mod test;
@@ -1,12 +1,13 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env, String};
+use soroban_sdk::{testutils::{Address as _, Ledger as _}, Env, String};
 
 #[test]
 fn test_create_stream() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -32,7 +33,8 @@ fn test_create_stream() {
 #[test]
 fn test_withdraw() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -59,7 +61,8 @@ fn test_withdraw() {
 #[test]
 fn test_pause_resume() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -80,7 +83,8 @@ fn test_pause_resume() {
 #[test]
 fn test_register_employee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -113,7 +117,8 @@ fn test_register_employee() {
 #[test]
 fn test_update_employee_info() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -150,7 +155,8 @@ fn test_update_employee_info() {
 #[test]
 fn test_get_employer_employees() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -197,7 +203,8 @@ fn test_get_employer_employees() {
 #[test]
 fn test_deactivate_employee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -230,7 +237,8 @@ fn test_deactivate_employee() {
 #[test]
 fn test_reactivate_employee() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -264,7 +272,8 @@ fn test_reactivate_employee() {
 #[test]
 fn test_update_employee_position() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, SalaryStreamingContract);
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
     let client = SalaryStreamingContractClient::new(&env, &contract_id);
 
     let employer = Address::generate(&env);
@@ -294,3 +303,230 @@ fn test_update_employee_position() {
     assert_eq!(employee_info.email, email);
     assert_eq!(employee_info.phone, phone);
 }
+
+mod mock_lending {
+    use soroban_sdk::{contract, contractimpl, Address, Env, Symbol, symbol_short};
+
+    const COLLECTED: Symbol = symbol_short!("COLLECTED");
+    const ENDED: Symbol = symbol_short!("ENDED");
+    const ENDED_STREAM: Symbol = symbol_short!("ENDED_STR");
+    const FAILING: Symbol = symbol_short!("FAILING");
+
+    /// Minimal stand-in for the lending contract's lien collection and employment hooks
+    #[contract]
+    pub struct MockLendingContract;
+
+    #[contractimpl]
+    impl MockLendingContract {
+        pub fn on_lien_collected(env: Env, _stream_id: u32, _employee: Address, amount: i128) {
+            let collected = Self::get_collected(env.clone()) + amount;
+            env.storage().persistent().set(&COLLECTED, &collected);
+        }
+
+        pub fn get_collected(env: Env) -> i128 {
+            env.storage().persistent().get(&COLLECTED).unwrap_or(0)
+        }
+
+        pub fn on_employment_ended(env: Env, _employee: Address, _employer: Address, stream_id: Option<u32>) {
+            if env.storage().persistent().get(&FAILING).unwrap_or(false) {
+                panic!("Lending hook failed");
+            }
+            let ended = Self::get_ended(env.clone()) + 1;
+            env.storage().persistent().set(&ENDED, &ended);
+            env.storage().persistent().set(&ENDED_STREAM, &stream_id);
+        }
+
+        pub fn get_ended_stream(env: Env) -> Option<u32> {
+            env.storage().persistent().get(&ENDED_STREAM).unwrap_or(None)
+        }
+
+        pub fn set_failing(env: Env, failing: bool) {
+            env.storage().persistent().set(&FAILING, &failing);
+        }
+
+        pub fn get_ended(env: Env) -> u32 {
+            env.storage().persistent().get(&ENDED).unwrap_or(0)
+        }
+    }
+}
+
+#[test]
+fn test_stream_lien() {
+    let env = Env::default();
+    let contract_id = env.register(SalaryStreamingContract, ());
+    let client = SalaryStreamingContractClient::new(&env, &contract_id);
+    let lender = env.register(mock_lending::MockLendingContract, ());
+    let lending = mock_lending::MockLendingContractClient::new(&env, &lender);
+
+    let admin = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    client.set_lending_contract(&admin, &lender);
+
+    let stream_id = client.create_stream(&employer, &employee, &1000i128, &100u64);
+    
+    // Lender claims the next 150 of accruals
+    client.place_lien(&lender, &stream_id, &150i128);
+    
+    // Fast forward time by 20 seconds (200 accrued)
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    
+    // Withdrawal pays the lien first, the employee gets the rest
+    assert!(client.try_withdraw(&stream_id, &100i128).is_err());
+    client.withdraw(&stream_id, &50i128);
+    assert_eq!(lending.get_collected(), 150i128);
+    assert!(client.get_stream_lien(&stream_id).is_none());
+    
+    // Lender can also collect directly, up to the requested maximum
+    client.place_lien(&lender, &stream_id, &100i128);
+    env.ledger().with_mut(|li| li.timestamp = 30);
+    let collected = client.collect_lien(&lender, &stream_id, &60i128);
+    assert_eq!(collected, 60i128);
+    let lien = client.get_stream_lien(&stream_id).unwrap();
+    assert_eq!(lien.collected, 60i128);
+    
+    // Next withdrawal settles the remainder and releases the stream
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    client.withdraw(&stream_id, &100i128);
+    assert_eq!(lending.get_collected(), 190i128);
+    assert!(client.get_stream_lien(&stream_id).is_none());
+    
+    let stream = client.get_stream(&stream_id);
+    assert_eq!(stream.withdrawn_amount, 400i128);
+}
+
+#[test]
+fn test_place_lien_requires_registered_lender() {
+    let env = Env::default();
+    let contract_id = env.register(SalaryStreamingContract, ());
+    let client = SalaryStreamingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let lender = Address::generate(&env);
+    let other = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    client.set_lending_contract(&admin, &lender);
+
+    let stream_id = client.create_stream(&employer, &employee, &1000i128, &100u64);
+    
+    assert!(client.try_place_lien(&other, &stream_id, &100i128).is_err());
+    assert!(client.try_set_lending_contract(&other, &other).is_err());
+}
+
+#[test]
+fn test_employment_ended_notifies_lending() {
+    let env = Env::default();
+    let contract_id = env.register(SalaryStreamingContract, ());
+    let client = SalaryStreamingContractClient::new(&env, &contract_id);
+    let lender = env.register(mock_lending::MockLendingContract, ());
+    let lending = mock_lending::MockLendingContractClient::new(&env, &lender);
+
+    let admin = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let name = String::from_str(&env, "John Doe");
+    let email = String::from_str(&env, "john@company.com");
+    let phone = String::from_str(&env, "+90 555 123 4567");
+    let position = String::from_str(&env, "Senior Developer");
+    let department = String::from_str(&env, "Engineering");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    client.set_lending_contract(&admin, &lender);
+    client.register_employee(&employer, &employee, &name, &email, &phone, &position, &department, &0u64);
+    let stream_id = client.create_stream(&employer, &employee, &1000i128, &100u64);
+    
+    // Ending the stream and deactivating the employee both notify the lender
+    client.end_stream(&stream_id);
+    assert_eq!(lending.get_ended(), 1);
+    assert_eq!(lending.get_ended_stream(), Some(stream_id));
+    client.deactivate_employee(&employer, &employee);
+    assert_eq!(lending.get_ended(), 2);
+    assert_eq!(lending.get_ended_stream(), None);
+    
+    // A failing lender doesn't stop the employer from ending streams
+    lending.set_failing(&true);
+    let stream_id = client.create_stream(&employer, &employee, &1000i128, &100u64);
+    client.end_stream(&stream_id);
+    assert!(!client.get_stream(&stream_id).is_active);
+}
+
+#[test]
+fn test_employment_history() {
+    let env = Env::default();
+    let contract_id = env.register(SalaryStreamingContract, ());
+    let client = SalaryStreamingContractClient::new(&env, &contract_id);
+
+    let first_employer = Address::generate(&env);
+    let second_employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let name = String::from_str(&env, "John Doe");
+    let email = String::from_str(&env, "john@company.com");
+    let phone = String::from_str(&env, "+90 555 123 4567");
+    let position = String::from_str(&env, "Developer");
+    let department = String::from_str(&env, "Engineering");
+
+    env.mock_all_auths();
+
+    client.register_employee(&first_employer, &employee, &name, &email, &phone, &position, &department, &1000u64);
+    
+    // Re-registering with the same employer keeps a single record
+    client.register_employee(&first_employer, &employee, &name, &email, &phone, &position, &department, &1000u64);
+    assert_eq!(client.get_employment_history(&employee).len(), 1);
+    
+    // Moving to a new employer closes the previous record
+    client.register_employee(&second_employer, &employee, &name, &email, &phone, &position, &department, &5000u64);
+    let history = client.get_employment_history(&employee);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().end_date, Some(5000u64));
+    assert!(history.get(1).unwrap().end_date.is_none());
+    
+    // Leaving and being rehired adds a new record
+    env.ledger().with_mut(|li| li.timestamp = 8000);
+    client.deactivate_employee(&second_employer, &employee);
+    env.ledger().with_mut(|li| li.timestamp = 9000);
+    client.reactivate_employee(&second_employer, &employee);
+    let history = client.get_employment_history(&employee);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(1).unwrap().end_date, Some(8000u64));
+    assert_eq!(history.get(2).unwrap().start_date, 9000u64);
+}
+
+#[test]
+fn test_stream_pause_tracking() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(SalaryStreamingContract, ());
+    let client = SalaryStreamingContractClient::new(&env, &contract_id);
+
+    let employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+
+    let stream_id = client.create_stream(&employer, &employee, &1000i128, &100u64);
+    let pauses = client.get_stream_pauses(&stream_id);
+    assert_eq!(pauses.pause_count, 0);
+    
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    client.pause_stream(&stream_id);
+    // Pausing an already paused stream is not a new interruption
+    client.pause_stream(&stream_id);
+    assert_eq!(client.get_stream_pauses(&stream_id).pause_count, 1);
+    
+    env.ledger().with_mut(|li| li.timestamp = 25);
+    client.resume_stream(&stream_id);
+    let pauses = client.get_stream_pauses(&stream_id);
+    assert_eq!(pauses.pause_count, 1);
+    assert_eq!(pauses.paused_seconds, 15);
+    assert_eq!(pauses.paused_at, None);
+    assert_eq!(pauses.last_resumed_at, 25);
+}
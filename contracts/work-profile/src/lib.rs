@@ -1,5 +1,143 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, Env};
+use soroban_sdk::{contract, contractclient, contractimpl, contracttype, vec, Address, Bytes, BytesN, Env, Map, String, Vec};
+
+/// Employee record as stored by the salary streaming contract
+#[derive(Clone)]
+#[contracttype]
+pub struct EmployeeInfo {
+    pub address: Address,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub position: String,
+    pub department: String,
+    pub employer: Address,
+    pub registration_time: u64,
+    pub start_date: u64,
+    pub end_date: Option<u64>,
+    pub is_active: bool,
+}
+
+/// Employment period as stored by the salary streaming contract
+#[derive(Clone)]
+#[contracttype]
+pub struct EmploymentRecord {
+    pub employer: Address,
+    pub start_date: u64,
+    pub end_date: Option<u64>,
+}
+
+/// Salary stream as stored by the salary streaming contract
+#[derive(Clone)]
+#[contracttype]
+pub struct SalaryStream {
+    pub id: u32,
+    pub employer: Address,
+    pub employee: Address,
+    pub total_amount: i128,
+    pub rate_per_second: i128,
+    pub start_time: u64,
+    pub duration_seconds: u64,
+    pub withdrawn_amount: i128,
+    pub is_active: bool,
+    pub is_paused: bool,
+}
+
+/// Stream interruptions as stored by the salary streaming contract
+#[derive(Clone)]
+#[contracttype]
+pub struct StreamPauses {
+    pub pause_count: u32,
+    pub paused_seconds: u64,
+    pub paused_at: Option<u64>,
+    pub last_resumed_at: u64,
+}
+
+/// Interface of the salary streaming contract used for on-chain employment facts
+#[contractclient(name = "SalaryStreamingClient")]
+pub trait SalaryStreamingInterface {
+    fn get_employee_info(env: Env, employee: Address) -> EmployeeInfo;
+    fn get_employment_history(env: Env, employee: Address) -> Vec<EmploymentRecord>;
+    fn get_employee_streams(env: Env, employee: Address) -> Vec<SalaryStream>;
+    fn get_stream_pauses(env: Env, stream_id: u32) -> StreamPauses;
+}
+
+const SECONDS_PER_MONTH: u64 = 30 * 24 * 60 * 60;
+
+// Best tier available to a profile past its expiry
+const STALE_TIER_FLOOR: u32 = 4;
+
+// Default time a profile stays current after it is first published or attested
+const DEFAULT_MAX_PROFILE_AGE: u64 = 365 * 24 * 60 * 60;
+
+// Maximum number of history entries returned per page
+const MAX_HISTORY_PAGE: u32 = 50;
+
+// Number of decreases in a self-reported value that flags a profile as suspicious
+const SUSPICIOUS_DECREASES: u32 = 2;
+
+// Version of the built-in scoring model used until an admin publishes one
+const DEFAULT_MODEL_VERSION: u32 = 1;
+
+// Best tier available to a profile that has not been attested
+const UNVERIFIED_TIER_FLOOR: u32 = 3;
+
+// Points awarded once a value reaches (or, for job changes, stays within) a threshold
+#[derive(Clone)]
+#[contracttype]
+pub struct ScoreBracket {
+    pub threshold: u32,
+    pub points: u32,
+}
+
+// Admin-managed scoring weights, brackets and tier cutoffs
+#[derive(Clone)]
+#[contracttype]
+pub struct ScoringModel {
+    pub version: u32,
+    pub base_score: u32,
+    pub experience_brackets: Vec<ScoreBracket>, // Years of experience, highest threshold first
+    pub tenure_brackets: Vec<ScoreBracket>,     // Months in current job, highest threshold first
+    pub job_change_brackets: Vec<ScoreBracket>, // Maximum job changes, lowest threshold first
+    pub job_change_penalty: u32,                // Points lost per job change above the last bracket
+    pub tier_cutoffs: Vec<u32>,                 // Minimum scores for tiers 1-4
+    pub stability_brackets: Vec<ScoreBracket>,  // Consecutive months streamed, highest threshold first
+    pub interruption_penalty: u32,              // Points lost per pause of a running stream
+    pub scores_credit_history: bool,            // Repayment history reported by lenders adjusts the score
+    pub scores_sector: bool,                    // The sector's risk adjustment applies to the score
+}
+
+// Contribution of each scoring factor to an employee's score
+#[derive(Clone)]
+#[contracttype]
+pub struct ScoreBreakdown {
+    pub model_version: u32,
+    pub base_score: u32,
+    pub experience_points: u32,
+    pub tenure_points: u32,
+    pub job_change_points: i32,    // Bonus, or penalty when negative
+    pub income_points: i32,        // Income stability, less interruptions
+    pub credit_adjustment: i32,    // Repayment history reported by lenders
+    pub sector_adjustment: i32,
+    pub risk_score: u32,
+    pub risk_tier: u32,
+    pub capped_unverified: bool,   // Tier held back until the profile is attested
+    pub stale: bool,               // Expired profile degraded to a conservative tier
+    pub disputed: bool,            // Employee has an open dispute over the profile's data
+    pub next_tier_score: Option<u32>,
+    pub next_experience_threshold: Option<u32>, // Years needed for the next experience bracket
+    pub next_tenure_threshold: Option<u32>,     // Months needed for the next tenure bracket
+}
+
+// Monthly income from an employee's running salary streams
+#[derive(Clone, PartialEq)]
+#[contracttype]
+pub struct VerifiedIncome {
+    pub monthly_income: i128,
+    pub active_streams: u32,
+    pub months_streamed: u32, // Longest uninterrupted run of a running stream
+    pub interruptions: u32,   // Pauses of the running streams
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -12,12 +150,159 @@ pub struct WorkProfile {
     pub risk_score: u32,
     pub risk_tier: u32,
     pub updated_at: u64,
+    pub verified: bool,                // Values attested by the employer or a verifier
+    pub attested_by: Option<Address>,
+    pub model_version: u32,            // Scoring model that produced the score
+    pub tenure_recorded_at: u64,       // When current_job_duration was last set; tenure grows from here
+    pub expires_at: u64,               // Profile is stale after this until re-attested
+    pub income: VerifiedIncome,        // Snapshot from salary streaming at the last update or refresh
+    pub sector_adjustment: i32,        // Sector's risk adjustment when the values were last set or recomputed
+}
+
+// Repayment behaviour reported by an authorized lender
+#[derive(Clone)]
+#[contracttype]
+pub enum CreditEvent {
+    OnTimePayment,
+    LatePayment,
+    Default,
+    Payoff,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CreditHistory {
+    pub on_time_payments: u32,
+    pub late_payments: u32,
+    pub defaults: u32,
+    pub payoffs: u32,
+    pub last_event_at: u64,
+}
+
+// Admin-registered sector an employee can report working in
+#[derive(Clone)]
+#[contracttype]
+pub struct Sector {
+    pub code: Bytes,
+    pub name: String,
+    pub risk_adjustment: i32, // Points added to (or taken from) the score of profiles in the sector
+    pub is_active: bool,
+}
+
+// Self-reported profile field, in commitment order
+#[derive(Clone, Copy, PartialEq)]
+#[contracttype]
+pub enum ProfileField {
+    YearsExperience,
+    CurrentJobDuration,
+    JobChanges,
+    Sector,
+}
+
+// Profile kept private behind a commitment to its fields
+// The commitment is sha256 over the four field hashes in ProfileField order,
+// each field hash being sha256(value || salt) with u32 values big-endian encoded.
+#[derive(Clone)]
+#[contracttype]
+pub struct PrivateProfile {
+    pub employee: Address,
+    pub commitment: BytesN<32>,
+    pub work_score: u32,       // Work history and sector score of the committed fields, vouched for by the attester
+    pub sector_adjustment: i32, // Committed sector's risk adjustment when the profile was committed
+    pub sector_adjustments: Map<Bytes, i32>, // Adjustment of each active sector when committed, by code
+    pub model_version: u32,
+    pub attested_by: Address,
+    pub committed_at: u64,
+    pub expires_at: u64,
+}
+
+// Preimage of one committed field
+#[derive(Clone)]
+#[contracttype]
+pub struct FieldOpening {
+    pub field: ProfileField,
+    pub value: Bytes,
+    pub salt: BytesN<32>,
+}
+
+// Profile value or credit history count an employee can dispute
+#[derive(Clone, Copy, PartialEq)]
+#[contracttype]
+pub enum DisputeField {
+    YearsExperience,
+    CurrentJobDuration,
+    JobChanges,
+    OnTimePayments,
+    LatePayments,
+    Defaults,
+    Payoffs,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[contracttype]
+pub enum DisputeStatus {
+    Open,
+    Corrected,
+    Rejected,
+}
+
+// Employee challenge to attested or reported data and its outcome
+#[derive(Clone)]
+#[contracttype]
+pub struct Dispute {
+    pub id: u32,
+    pub employee: Address,
+    pub field: DisputeField,
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub opened_at: u64,
+    pub resolved_by: Option<Address>,
+    pub resolved_at: Option<u64>,
+    pub corrected_value: Option<u32>, // Value written by the arbiter when the dispute is upheld
+}
+
+// Snapshot of a profile written to the append-only history
+#[derive(Clone)]
+#[contracttype]
+pub struct ProfileVersion {
+    pub version: u32,
+    pub profile: WorkProfile,
+    pub changed_by: Address,   // Employee, attester, reporter, or this contract for permissionless refreshes
+    pub changed_at: u64,
+}
+
+// Self-reported changes that can't happen in a genuine work history
+#[derive(Clone)]
+#[contracttype]
+pub struct ProfileAlerts {
+    pub job_change_decreases: u32,
+    pub experience_decreases: u32,
+    pub suspicious: bool,
 }
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Profile(Address),
+    Admin,
+    Reporter(Address),
+    CreditHistory(Address),
+    Verifier(Address),
+    StreamingContract,
+    ScoringModel(u32),
+    ScoringModelVersion,
+    ProfileVersion(Address, u32),
+    ProfileVersionCount(Address),
+    ProfileAlerts(Address),
+    Sector(Bytes),
+    SectorCodes,
+    MaxProfileAge,
+    PrivateProfile(Address),
+    Arbiter(Address),
+    Dispute(u32),
+    DisputeCounter,
+    EmployeeDisputes(Address),
+    OpenDisputes(Address),
 }
 
 #[contract]
@@ -25,6 +310,365 @@ pub struct WorkProfileContract;
 
 #[contractimpl]
 impl WorkProfileContract {
+    /// Initializes the contract with an admin who manages credit reporters
+    pub fn initialize(env: Env, admin: Address) {
+        if env.storage().persistent().has(&DataKey::Admin) {
+            panic!("Already initialized");
+        }
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+    }
+    
+    /// Authorizes a contract to report credit events (admin function)
+    pub fn add_reporter(env: Env, admin: Address, reporter: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().set(&DataKey::Reporter(reporter), &true);
+    }
+    
+    /// Revokes a credit reporter (admin function)
+    pub fn remove_reporter(env: Env, admin: Address, reporter: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().remove(&DataKey::Reporter(reporter));
+    }
+    
+    /// Adds a sector to the taxonomy or updates its name and risk adjustment (admin function)
+    /// Profiles keep the adjustment they were scored with until updated or recomputed.
+    pub fn register_sector(env: Env, admin: Address, code: Bytes, name: String, risk_adjustment: i32) {
+        Self::require_admin(&env, &admin);
+        
+        if code.is_empty() {
+            panic!("Sector code cannot be empty");
+        }
+        if !(-50..=50).contains(&risk_adjustment) {
+            panic!("Sector adjustment must be between -50 and 50");
+        }
+        
+        match Self::get_sector(env.clone(), code.clone()) {
+            Some(existing) if !existing.is_active => panic!("Sector is retired"),
+            Some(_) => {},
+            None => {
+                let mut codes = Self::get_sector_codes(&env);
+                codes.push_back(code.clone());
+                env.storage().persistent().set(&DataKey::SectorCodes, &codes);
+            },
+        }
+        
+        let sector = Sector {
+            code: code.clone(),
+            name,
+            risk_adjustment,
+            is_active: true,
+        };
+        env.storage().persistent().set(&DataKey::Sector(code), &sector);
+    }
+    
+    /// Retires a sector so new profiles can't use it (admin function)
+    /// Existing profiles keep the sector and its adjustment.
+    pub fn retire_sector(env: Env, admin: Address, code: Bytes) {
+        Self::require_admin(&env, &admin);
+        
+        let mut sector = Self::get_sector(env.clone(), code.clone()).expect("Sector not found");
+        sector.is_active = false;
+        env.storage().persistent().set(&DataKey::Sector(code), &sector);
+    }
+    
+    /// Makes a retired sector available to new profiles again (admin function)
+    pub fn reactivate_sector(env: Env, admin: Address, code: Bytes) {
+        Self::require_admin(&env, &admin);
+        
+        let mut sector = Self::get_sector(env.clone(), code.clone()).expect("Sector not found");
+        if sector.is_active {
+            panic!("Sector is already active");
+        }
+        sector.is_active = true;
+        env.storage().persistent().set(&DataKey::Sector(code), &sector);
+    }
+    
+    /// Gets a sector by code
+    pub fn get_sector(env: Env, code: Bytes) -> Option<Sector> {
+        env.storage().persistent().get(&DataKey::Sector(code))
+    }
+    
+    /// Gets all active sectors
+    pub fn get_sectors(env: Env) -> Vec<Sector> {
+        let mut sectors = Vec::new(&env);
+        
+        for code in Self::get_sector_codes(&env).iter() {
+            if let Some(sector) = Self::get_sector(env.clone(), code) {
+                if sector.is_active {
+                    sectors.push_back(sector);
+                }
+            }
+        }
+        
+        sectors
+    }
+    
+    /// Registers a verifier allowed to attest any employee's profile (admin function)
+    pub fn add_verifier(env: Env, admin: Address, verifier: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().set(&DataKey::Verifier(verifier), &true);
+    }
+    
+    /// Removes a registered verifier (admin function)
+    pub fn remove_verifier(env: Env, admin: Address, verifier: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().remove(&DataKey::Verifier(verifier));
+    }
+    
+    /// Checks if an address is a registered verifier
+    pub fn is_verifier(env: Env, verifier: Address) -> bool {
+        env.storage().persistent().get(&DataKey::Verifier(verifier)).unwrap_or(false)
+    }
+    
+    /// Registers an arbiter who resolves profile disputes (admin function)
+    pub fn add_arbiter(env: Env, admin: Address, arbiter: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().set(&DataKey::Arbiter(arbiter), &true);
+    }
+    
+    /// Removes a registered arbiter (admin function)
+    pub fn remove_arbiter(env: Env, admin: Address, arbiter: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().remove(&DataKey::Arbiter(arbiter));
+    }
+    
+    /// Checks if an address is a registered arbiter
+    pub fn is_arbiter(env: Env, arbiter: Address) -> bool {
+        env.storage().persistent().get(&DataKey::Arbiter(arbiter)).unwrap_or(false)
+    }
+    
+    /// Sets the salary streaming contract used to look up employers (admin function)
+    pub fn set_streaming_contract(env: Env, admin: Address, streaming_contract: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().persistent().set(&DataKey::StreamingContract, &streaming_contract);
+    }
+    
+    /// Attests the employee's current profile values
+    /// The attester must be a registered verifier or the employee's active employer in salary streaming,
+    /// and passes the values they checked, which must match the values stored by the last update.
+    pub fn attest_profile(
+        env: Env,
+        attester: Address,
+        employee: Address,
+        years_experience: u32,
+        current_job_duration: u32,
+        job_changes: u32,
+        sector: Bytes,
+    ) {
+        attester.require_auth();
+        
+        if !Self::is_verifier(env.clone(), attester.clone()) && !Self::is_current_employer(&env, &attester, &employee) {
+            panic!("Not authorized to attest this profile");
+        }
+        
+        let mut profile = Self::get_profile(env.clone(), employee).expect("Profile not found");
+        if profile.years_experience != years_experience
+            || profile.current_job_duration != current_job_duration
+            || profile.job_changes != job_changes
+            || profile.sector != sector
+        {
+            panic!("Attested values do not match profile");
+        }
+        profile.verified = true;
+        profile.attested_by = Some(attester.clone());
+        profile.expires_at = env.ledger().timestamp() + Self::get_max_profile_age(env.clone());
+        
+        // Keeps the model the score was produced with
+        let model = Self::get_scoring_model_version(env.clone(), profile.model_version);
+        Self::rescore(&env, &mut profile, &model);
+        Self::store_profile(&env, &profile, &attester);
+    }
+    
+    /// Checks if an employee's profile values have been attested
+    /// Expired attestations no longer count. Private profiles are attested when committed.
+    pub fn is_profile_verified(env: Env, employee: Address) -> bool {
+        let now = env.ledger().timestamp();
+        if let Some(private) = Self::get_private_profile(env.clone(), employee.clone()) {
+            return now <= private.expires_at;
+        }
+        Self::get_profile(env, employee).is_some_and(|profile| profile.verified && now <= profile.expires_at)
+    }
+    
+    /// Stores an attested commitment to the employee's profile instead of its values
+    /// The attester has seen the values off-chain and vouches for the work score they produce
+    /// under the active model, including the committed sector's current risk adjustment.
+    /// Requires both the employee and the attester.
+    pub fn commit_profile(env: Env, attester: Address, employee: Address, commitment: BytesN<32>, work_score: u32, sector_adjustment: i32) {
+        attester.require_auth();
+        employee.require_auth();
+        
+        if !Self::is_verifier(env.clone(), attester.clone()) && !Self::is_current_employer(&env, &attester, &employee) {
+            panic!("Not authorized to attest this profile");
+        }
+        if Self::get_profile(env.clone(), employee.clone()).is_some() {
+            panic!("Profile is already public");
+        }
+        if work_score > 100 {
+            panic!("Work score must be at most 100");
+        }
+        
+        // The adjustment must belong to an active sector, or be zero until a taxonomy is registered
+        // Active sectors' adjustments are kept so an opened sector can be checked against it later.
+        let mut sector_adjustments = Map::new(&env);
+        for sector in Self::get_sectors(env.clone()).iter() {
+            sector_adjustments.set(sector.code, sector.risk_adjustment);
+        }
+        let known = if sector_adjustments.is_empty() {
+            sector_adjustment == 0
+        } else {
+            sector_adjustments.values().contains(sector_adjustment)
+        };
+        if !known {
+            panic!("Unknown sector adjustment");
+        }
+        
+        let private = PrivateProfile {
+            employee: employee.clone(),
+            commitment,
+            work_score,
+            sector_adjustment,
+            sector_adjustments,
+            model_version: Self::get_scoring_model(env.clone()).version,
+            attested_by: attester,
+            committed_at: env.ledger().timestamp(),
+            expires_at: env.ledger().timestamp() + Self::get_max_profile_age(env.clone()),
+        };
+        env.storage().persistent().set(&DataKey::PrivateProfile(employee), &private);
+    }
+    
+    /// Gets an employee's private profile commitment
+    pub fn get_private_profile(env: Env, employee: Address) -> Option<PrivateProfile> {
+        env.storage().persistent().get(&DataKey::PrivateProfile(employee))
+    }
+    
+    /// Checks openings of committed fields, for lenders to simulate off-chain
+    /// `field_hashes` are the four field hashes in ProfileField order; each opening must match its hash.
+    /// The committed work score must be reachable from the opened values, and match exactly once all are open.
+    /// An opened sector must have had the committed sector adjustment when the profile was committed.
+    pub fn verify_opening(env: Env, employee: Address, field_hashes: Vec<BytesN<32>>, openings: Vec<FieldOpening>) -> bool {
+        let private = Self::get_private_profile(env.clone(), employee).expect("Private profile not found");
+        if field_hashes.len() != 4 {
+            return false;
+        }
+        
+        // Field hashes must reproduce the commitment
+        let mut preimage = Bytes::new(&env);
+        for hash in field_hashes.iter() {
+            preimage.append(&hash.into());
+        }
+        if env.crypto().sha256(&preimage).to_bytes() != private.commitment {
+            return false;
+        }
+        
+        for opening in openings.iter() {
+            if Self::hash_field(env.clone(), opening.value.clone(), opening.salt.clone()) != field_hashes.get_unchecked(Self::field_index(opening.field)) {
+                return false;
+            }
+        }
+        
+        // An opened sector must be the one whose adjustment went into the committed score
+        if let Some(code) = Self::opened_value(&openings, ProfileField::Sector) {
+            let adjustment = if private.sector_adjustments.is_empty() {
+                Some(0)
+            } else {
+                private.sector_adjustments.get(code)
+            };
+            if adjustment != Some(private.sector_adjustment) {
+                return false;
+            }
+        }
+        
+        // Unopened fields may take any value, so bound the score by their worst and best cases
+        let opened_u32 = |field| Self::opened_value(&openings, field).map(|value| Self::decode_u32(&value));
+        let (years, duration, changes) = (
+            opened_u32(ProfileField::YearsExperience),
+            opened_u32(ProfileField::CurrentJobDuration),
+            opened_u32(ProfileField::JobChanges),
+        );
+        if [years, duration, changes].contains(&Some(None)) {
+            return false;
+        }
+        let (years, duration, changes) = (years.flatten(), duration.flatten(), changes.flatten());
+        
+        let model = Self::get_scoring_model_version(env.clone(), private.model_version);
+        let sector_adjustment = if model.scores_sector { private.sector_adjustment } else { 0 };
+        let score = |years, duration, changes| {
+            let work = Self::work_breakdown(&model, years, duration, changes);
+            (work.risk_score as i32 + sector_adjustment).clamp(0, 100) as u32
+        };
+        let lowest = score(years.unwrap_or(0), duration.unwrap_or(0), changes.unwrap_or(u32::MAX));
+        let highest = score(years.unwrap_or(u32::MAX), duration.unwrap_or(u32::MAX), changes.unwrap_or(0));
+        
+        (lowest..=highest).contains(&private.work_score)
+    }
+    
+    /// Hashes a field value with its salt as used in profile commitments
+    pub fn hash_field(env: Env, value: Bytes, salt: BytesN<32>) -> BytesN<32> {
+        let mut preimage = value;
+        preimage.append(&salt.into());
+        env.crypto().sha256(&preimage).to_bytes()
+    }
+    
+    /// Sets how long a profile stays current after it is first published or attested (admin function)
+    pub fn set_max_profile_age(env: Env, admin: Address, max_age: u64) {
+        Self::require_admin(&env, &admin);
+        
+        if max_age == 0 {
+            panic!("Max profile age must be positive");
+        }
+        
+        env.storage().persistent().set(&DataKey::MaxProfileAge, &max_age);
+    }
+    
+    /// Gets how long a profile stays current after it is first published or attested
+    pub fn get_max_profile_age(env: Env) -> u64 {
+        env.storage().persistent().get(&DataKey::MaxProfileAge).unwrap_or(DEFAULT_MAX_PROFILE_AGE)
+    }
+    
+    /// Checks if an address is an authorized credit reporter
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().get(&DataKey::Reporter(reporter)).unwrap_or(false)
+    }
+    
+    /// Records a repayment event from an authorized reporter and rescores the employee's profile
+    pub fn report_credit_event(env: Env, reporter: Address, employee: Address, event: CreditEvent) {
+        reporter.require_auth();
+        
+        if !Self::is_reporter(env.clone(), reporter.clone()) {
+            panic!("Unauthorized reporter");
+        }
+        
+        let mut history = Self::get_credit_history(env.clone(), employee.clone());
+        match event {
+            CreditEvent::OnTimePayment => history.on_time_payments += 1,
+            CreditEvent::LatePayment => history.late_payments += 1,
+            CreditEvent::Default => history.defaults += 1,
+            CreditEvent::Payoff => history.payoffs += 1,
+        }
+        history.last_event_at = env.ledger().timestamp();
+        env.storage().persistent().set(&DataKey::CreditHistory(employee.clone()), &history);
+        
+        // Existing profiles pick up the new credit history immediately, under the model they were scored with
+        if let Some(mut profile) = Self::get_profile(env.clone(), employee.clone()) {
+            let model = Self::get_scoring_model_version(env.clone(), profile.model_version);
+            Self::rescore(&env, &mut profile, &model);
+            Self::store_profile(&env, &profile, &reporter);
+        }
+    }
+    
+    /// Gets the reported credit history for an employee
+    pub fn get_credit_history(env: Env, employee: Address) -> CreditHistory {
+        env.storage().persistent()
+            .get(&DataKey::CreditHistory(employee))
+            .unwrap_or(CreditHistory {
+                on_time_payments: 0,
+                late_payments: 0,
+                defaults: 0,
+                payoffs: 0,
+                last_event_at: 0,
+            })
+    }
+    
     /// Updates work profile for an employee
     pub fn update_profile(
         env: Env,
@@ -37,22 +681,362 @@ impl WorkProfileContract {
         // Require authorization from employee
         employee.require_auth();
         
-        // Calculate risk score
-        let risk_score = Self::calculate_risk_score(years_experience, current_job_duration, job_changes);
-        let risk_tier = Self::get_risk_tier(risk_score);
+        // Sector must come from the registered taxonomy, once the admin has registered one
+        let registered = Self::get_sector(env.clone(), sector.clone());
+        if !Self::get_sector_codes(&env).is_empty() && !registered.as_ref().is_some_and(|registered| registered.is_active) {
+            panic!("Unknown sector");
+        }
+        
+        // Publishing values replaces a private profile
+        env.storage().persistent().remove(&DataKey::PrivateProfile(employee.clone()));
+        
+        // Only attestation extends an existing profile's expiry
+        let previous = Self::get_profile(env.clone(), employee.clone());
+        let expires_at = match &previous {
+            Some(previous) => previous.expires_at,
+            None => env.ledger().timestamp() + Self::get_max_profile_age(env.clone()),
+        };
         
-        let profile = WorkProfile {
+        // Self-reported values are unverified until attested again
+        let mut profile = WorkProfile {
             employee: employee.clone(),
             years_experience,
             current_job_duration,
             job_changes,
             sector,
-            risk_score,
-            risk_tier,
+            risk_score: 0,
+            risk_tier: 5,
             updated_at: env.ledger().timestamp(),
+            verified: false,
+            attested_by: None,
+            model_version: 0,
+            tenure_recorded_at: env.ledger().timestamp(),
+            expires_at,
+            income: Self::income_snapshot(&env, &employee),
+            sector_adjustment: registered.map_or(0, |registered| registered.risk_adjustment),
+        };
+        
+        // Calculate risk score with the active model
+        Self::rescore(&env, &mut profile, &Self::get_scoring_model(env.clone()));
+        
+        // Track self-reported values going backwards
+        if let Some(previous) = previous {
+            let mut alerts = Self::get_profile_alerts(env.clone(), employee.clone());
+            if job_changes < previous.job_changes {
+                alerts.job_change_decreases += 1;
+            }
+            if years_experience < previous.years_experience {
+                alerts.experience_decreases += 1;
+            }
+            alerts.suspicious = alerts.job_change_decreases >= SUSPICIOUS_DECREASES
+                || alerts.experience_decreases >= SUSPICIOUS_DECREASES;
+            env.storage().persistent().set(&DataKey::ProfileAlerts(employee.clone()), &alerts);
+        }
+        
+        Self::store_profile(&env, &profile, &employee);
+    }
+    
+    /// Recomputes an employee's tenure, job changes and score from salary streaming records
+    /// Callable by anyone; self-reported experience and sector are kept. Values that change lose
+    /// their attestation, and nothing is stored when the values, score, tier and model are unchanged.
+    pub fn refresh_profile(env: Env, employee: Address) {
+        let mut profile = Self::get_profile(env.clone(), employee.clone()).expect("Profile not found");
+        let previous = profile.clone();
+        let streaming_contract: Address = env.storage().persistent()
+            .get(&DataKey::StreamingContract)
+            .expect("Streaming contract not configured");
+        let streaming = SalaryStreamingClient::new(&env, &streaming_contract);
+        let now = env.ledger().timestamp();
+        
+        // Tenure runs from the latest employment's start to today, or to the end date once employment has ended
+        // Rehiring starts a new record, so the current employment is the last one rather than the first start date.
+        let info = streaming.get_employee_info(&employee);
+        let history = streaming.get_employment_history(&employee);
+        let (start_date, end_date) = history.last().map_or((info.start_date, info.end_date), |record| (record.start_date, record.end_date));
+        let tenure_end = if info.is_active {
+            now
+        } else {
+            end_date.unwrap_or(start_date)
+        };
+        let current_job_duration = (tenure_end.saturating_sub(start_date) / SECONDS_PER_MONTH) as u32;
+        
+        // Each move to a different employer counts as a job change
+        let mut job_changes = 0u32;
+        for i in 1..history.len() {
+            if history.get_unchecked(i).employer != history.get_unchecked(i - 1).employer {
+                job_changes += 1;
+            }
+        }
+        let income = Self::get_verified_income(env.clone(), employee);
+        
+        // Stored tenure grows with elapsed time, so compare against its value as of now
+        let elapsed_months = now.min(previous.expires_at).saturating_sub(previous.tenure_recorded_at) / SECONDS_PER_MONTH;
+        let recorded_duration = previous.current_job_duration.saturating_add(elapsed_months as u32);
+        let values_changed = current_job_duration != recorded_duration
+            || job_changes != previous.job_changes
+            || income != previous.income;
+        if values_changed {
+            profile.current_job_duration = current_job_duration;
+            profile.job_changes = job_changes;
+            profile.tenure_recorded_at = now;
+            profile.income = income;
+            profile.verified = false;
+            profile.attested_by = None;
+        }
+        
+        Self::rescore(&env, &mut profile, &Self::get_scoring_model(env.clone()));
+        if !values_changed
+            && profile.risk_score == previous.risk_score
+            && profile.risk_tier == previous.risk_tier
+            && profile.model_version == previous.model_version
+        {
+            return;
+        }
+        Self::store_profile(&env, &profile, &env.current_contract_address());
+    }
+    
+    /// Computes an employee's monthly income and its stability from running salary streams
+    pub fn get_verified_income(env: Env, employee: Address) -> VerifiedIncome {
+        let streaming_contract: Address = env.storage().persistent()
+            .get(&DataKey::StreamingContract)
+            .expect("Streaming contract not configured");
+        let streaming = SalaryStreamingClient::new(&env, &streaming_contract);
+        let now = env.ledger().timestamp();
+        
+        let mut income = Self::no_income();
+        for stream in streaming.get_employee_streams(&employee).iter() {
+            // Only streams still paying out count towards income
+            let stream_end = stream.start_time.saturating_add(stream.duration_seconds);
+            if !stream.is_active || stream.is_paused || now >= stream_end {
+                continue;
+            }
+            
+            let pauses = streaming.get_stream_pauses(&stream.id);
+            let run_start = stream.start_time.max(pauses.last_resumed_at);
+            let months = (now.saturating_sub(run_start) / SECONDS_PER_MONTH) as u32;
+            
+            income.monthly_income += stream.rate_per_second * SECONDS_PER_MONTH as i128;
+            income.active_streams += 1;
+            income.months_streamed = income.months_streamed.max(months);
+            income.interruptions += pauses.pause_count;
+        }
+        
+        income
+    }
+    
+    /// Rescores an employee's profile with the active scoring model and sector adjustment
+    /// Callable by anyone; profiles otherwise keep the model version and adjustment they were scored with.
+    /// Nothing is stored when the score, tier, model and adjustment are unchanged.
+    pub fn recompute_profile(env: Env, employee: Address) {
+        let mut profile = Self::get_profile(env.clone(), employee.clone()).expect("Profile not found");
+        let previous = profile.clone();
+        
+        profile.sector_adjustment = Self::get_sector(env.clone(), profile.sector.clone()).map_or(0, |sector| sector.risk_adjustment);
+        Self::rescore(&env, &mut profile, &Self::get_scoring_model(env.clone()));
+        if profile.risk_score == previous.risk_score
+            && profile.risk_tier == previous.risk_tier
+            && profile.model_version == previous.model_version
+            && profile.sector_adjustment == previous.sector_adjustment
+        {
+            return;
+        }
+        Self::store_profile(&env, &profile, &env.current_contract_address());
+    }
+    
+    /// Publishes a new scoring model as the active one (admin function)
+    /// The version is assigned by the contract. Returns the new version.
+    pub fn set_scoring_model(env: Env, admin: Address, model: ScoringModel) -> u32 {
+        Self::require_admin(&env, &admin);
+        
+        if model.tier_cutoffs.len() != 4 {
+            panic!("Tier cutoffs must cover tiers 1-4");
+        }
+        for i in 1..model.tier_cutoffs.len() {
+            if model.tier_cutoffs.get_unchecked(i) >= model.tier_cutoffs.get_unchecked(i - 1) {
+                panic!("Tier cutoffs must be descending");
+            }
+        }
+        for i in 1..model.experience_brackets.len() {
+            if model.experience_brackets.get_unchecked(i).threshold >= model.experience_brackets.get_unchecked(i - 1).threshold {
+                panic!("Experience brackets must be descending");
+            }
+        }
+        for i in 1..model.tenure_brackets.len() {
+            if model.tenure_brackets.get_unchecked(i).threshold >= model.tenure_brackets.get_unchecked(i - 1).threshold {
+                panic!("Tenure brackets must be descending");
+            }
+        }
+        for i in 1..model.job_change_brackets.len() {
+            if model.job_change_brackets.get_unchecked(i).threshold <= model.job_change_brackets.get_unchecked(i - 1).threshold {
+                panic!("Job change brackets must be ascending");
+            }
+        }
+        for i in 1..model.stability_brackets.len() {
+            if model.stability_brackets.get_unchecked(i).threshold >= model.stability_brackets.get_unchecked(i - 1).threshold {
+                panic!("Stability brackets must be descending");
+            }
+        }
+        
+        let version: u32 = env.storage().persistent()
+            .get(&DataKey::ScoringModelVersion)
+            .unwrap_or(DEFAULT_MODEL_VERSION) + 1;
+        let model = ScoringModel { version, ..model };
+        env.storage().persistent().set(&DataKey::ScoringModel(version), &model);
+        env.storage().persistent().set(&DataKey::ScoringModelVersion, &version);
+        
+        version
+    }
+    
+    /// Gets the active scoring model
+    pub fn get_scoring_model(env: Env) -> ScoringModel {
+        let version: u32 = env.storage().persistent()
+            .get(&DataKey::ScoringModelVersion)
+            .unwrap_or(DEFAULT_MODEL_VERSION);
+        Self::get_scoring_model_version(env, version)
+    }
+    
+    /// Gets a scoring model by version
+    pub fn get_scoring_model_version(env: Env, version: u32) -> ScoringModel {
+        if version == DEFAULT_MODEL_VERSION {
+            return Self::default_model(&env);
+        }
+        env.storage().persistent()
+            .get(&DataKey::ScoringModel(version))
+            .expect("Scoring model not found")
+    }
+    
+    /// Gets a page of an employee's profile versions, oldest first, starting at the cursor
+    pub fn get_profile_history(env: Env, employee: Address, cursor: u32, limit: u32) -> Vec<ProfileVersion> {
+        let count = Self::get_profile_version_count(env.clone(), employee.clone());
+        let end = count.min(cursor.saturating_add(limit.min(MAX_HISTORY_PAGE)));
+        let mut versions = Vec::new(&env);
+        
+        for version in cursor..end {
+            if let Some(entry) = env.storage().persistent().get(&DataKey::ProfileVersion(employee.clone(), version)) {
+                versions.push_back(entry);
+            }
+        }
+        
+        versions
+    }
+    
+    /// Gets the number of recorded profile versions for an employee
+    pub fn get_profile_version_count(env: Env, employee: Address) -> u32 {
+        env.storage().persistent().get(&DataKey::ProfileVersionCount(employee)).unwrap_or(0)
+    }
+    
+    /// Gets suspicious change counters for an employee's self-reported values
+    pub fn get_profile_alerts(env: Env, employee: Address) -> ProfileAlerts {
+        env.storage().persistent()
+            .get(&DataKey::ProfileAlerts(employee))
+            .unwrap_or(ProfileAlerts {
+                job_change_decreases: 0,
+                experience_decreases: 0,
+                suspicious: false,
+            })
+    }
+    
+    /// Opens a dispute over a profile value or credit history count
+    /// Profile values can only be disputed on a public profile. Returns the dispute ID.
+    pub fn open_dispute(env: Env, employee: Address, field: DisputeField, reason: String) -> u32 {
+        employee.require_auth();
+        
+        if reason.is_empty() {
+            panic!("Dispute reason cannot be empty");
+        }
+        if Self::is_profile_field(field) && Self::get_profile(env.clone(), employee.clone()).is_none() {
+            panic!("Profile not found");
+        }
+        
+        let mut dispute_ids: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::EmployeeDisputes(employee.clone()))
+            .unwrap_or_else(|| Vec::new(&env));
+        for dispute_id in dispute_ids.iter() {
+            let existing = Self::get_dispute(env.clone(), dispute_id);
+            if existing.field == field && existing.status == DisputeStatus::Open {
+                panic!("Dispute already open for this field");
+            }
+        }
+        
+        let dispute_id: u32 = env.storage().persistent().get(&DataKey::DisputeCounter).unwrap_or(0) + 1;
+        let dispute = Dispute {
+            id: dispute_id,
+            employee: employee.clone(),
+            field,
+            reason,
+            status: DisputeStatus::Open,
+            opened_at: env.ledger().timestamp(),
+            resolved_by: None,
+            resolved_at: None,
+            corrected_value: None,
         };
+        env.storage().persistent().set(&DataKey::Dispute(dispute_id), &dispute);
+        env.storage().persistent().set(&DataKey::DisputeCounter, &dispute_id);
+        
+        dispute_ids.push_back(dispute_id);
+        env.storage().persistent().set(&DataKey::EmployeeDisputes(employee.clone()), &dispute_ids);
+        
+        let open = Self::get_open_dispute_count(&env, &employee);
+        env.storage().persistent().set(&DataKey::OpenDisputes(employee), &(open + 1));
+        
+        dispute_id
+    }
+    
+    /// Resolves an open dispute (admin or arbiter function)
+    /// A corrected value upholds the dispute and is written to the profile or credit history;
+    /// no value rejects it.
+    pub fn resolve_dispute(env: Env, arbiter: Address, dispute_id: u32, corrected_value: Option<u32>) {
+        arbiter.require_auth();
+        
+        let admin: Address = env.storage().persistent().get(&DataKey::Admin).expect("Not initialized");
+        if arbiter != admin && !Self::is_arbiter(env.clone(), arbiter.clone()) {
+            panic!("Not authorized to resolve disputes");
+        }
+        
+        let mut dispute = Self::get_dispute(env.clone(), dispute_id);
+        if dispute.status != DisputeStatus::Open {
+            panic!("Dispute is not open");
+        }
         
-        env.storage().persistent().set(&DataKey::Profile(employee), &profile);
+        if let Some(value) = corrected_value {
+            Self::apply_correction(&env, &dispute.employee, dispute.field, value, &arbiter);
+            dispute.status = DisputeStatus::Corrected;
+        } else {
+            dispute.status = DisputeStatus::Rejected;
+        }
+        dispute.resolved_by = Some(arbiter);
+        dispute.resolved_at = Some(env.ledger().timestamp());
+        dispute.corrected_value = corrected_value;
+        env.storage().persistent().set(&DataKey::Dispute(dispute_id), &dispute);
+        
+        let open = Self::get_open_dispute_count(&env, &dispute.employee);
+        env.storage().persistent().set(&DataKey::OpenDisputes(dispute.employee), &open.saturating_sub(1));
+    }
+    
+    /// Gets a dispute by ID
+    pub fn get_dispute(env: Env, dispute_id: u32) -> Dispute {
+        env.storage().persistent()
+            .get(&DataKey::Dispute(dispute_id))
+            .expect("Dispute not found")
+    }
+    
+    /// Gets all disputes opened by an employee, oldest first
+    pub fn get_employee_disputes(env: Env, employee: Address) -> Vec<Dispute> {
+        let dispute_ids: Vec<u32> = env.storage().persistent()
+            .get(&DataKey::EmployeeDisputes(employee))
+            .unwrap_or_else(|| Vec::new(&env));
+        let mut disputes = Vec::new(&env);
+        
+        for dispute_id in dispute_ids.iter() {
+            disputes.push_back(Self::get_dispute(env.clone(), dispute_id));
+        }
+        
+        disputes
+    }
+    
+    /// Checks if an employee has an open dispute over their profile or credit history
+    pub fn is_profile_disputed(env: Env, employee: Address) -> bool {
+        Self::get_open_dispute_count(&env, &employee) > 0
     }
     
     /// Gets work profile for an employee
@@ -60,81 +1044,356 @@ impl WorkProfileContract {
         env.storage().persistent().get(&DataKey::Profile(employee))
     }
     
-    /// Calculates risk score based on work history
-    pub    fn calculate_risk_score(
+    /// Calculates risk score based on work history with the active scoring model
+    pub fn calculate_risk_score(
+        env: Env,
         years_experience: u32,
         current_job_duration: u32,
         job_changes: u32,
     ) -> u32 {
-        let mut score = 50u32; // Base score
-        
-        // Years of experience (0-40 points)
-        if years_experience >= 8 {
-            score = score.saturating_add(40);
-        } else if years_experience >= 5 {
-            score = score.saturating_add(30);
-        } else if years_experience >= 3 {
-            score = score.saturating_add(20);
-        } else if years_experience >= 1 {
-            score = score.saturating_add(10);
-        }
-        
-        // Current job duration (0-30 points)
-        if current_job_duration >= 24 {
-            score = score.saturating_add(30);
-        } else if current_job_duration >= 12 {
-            score = score.saturating_add(20);
-        } else if current_job_duration >= 6 {
-            score = score.saturating_add(10);
-        }
-        
-        // Job changes penalty/bonus (0-20 points)
-        if job_changes <= 1 {
-            score = score.saturating_add(20);
-        } else if job_changes <= 2 {
-            score = score.saturating_add(10);
-        } else if job_changes <= 3 {
-            score = score.saturating_add(5);
-        } else {
-            // Penalty for frequent job changes
-            let penalty = (job_changes - 3) * 5;
-            score = score.saturating_sub(penalty);
+        let model = Self::get_scoring_model(env);
+        Self::work_breakdown(&model, years_experience, current_job_duration, job_changes).risk_score
+    }
+    
+    /// Explains an employee's stored score factor by factor under the model that produced it
+    /// Includes the score needed for the next tier and the next experience and tenure brackets.
+    pub fn explain_score(env: Env, employee: Address) -> ScoreBreakdown {
+        let profile = Self::get_profile(env.clone(), employee).expect("Profile not found");
+        Self::current_breakdown(&env, &profile)
+    }
+    
+    /// Calculates the credit history adjustment to the work history score
+    pub fn calculate_credit_adjustment(history: CreditHistory) -> i32 {
+        // On-time payments and payoffs (up to +10 points each)
+        let on_time_bonus = (history.on_time_payments as i32 * 2).min(10);
+        let payoff_bonus = (history.payoffs as i32 * 5).min(10);
+        
+        // Late payments (up to -20 points) and defaults (-25 points each)
+        let late_penalty = (history.late_payments as i32 * 5).min(20);
+        let default_penalty = (history.defaults as i32).saturating_mul(25);
+        
+        on_time_bonus + payoff_bonus - late_penalty - default_penalty
+    }
+    
+    /// Gets risk tier based on score with the active scoring model
+    pub fn get_risk_tier(env: Env, risk_score: u32) -> u32 {
+        Self::model_tier(&Self::get_scoring_model(env), risk_score)
+    }
+    
+    /// Gets risk tier directly for an employee
+    /// Reflects tenure accrued since the profile was recorded and degrades expired profiles.
+    pub fn get_employee_risk_tier(env: Env, employee: Address) -> u32 {
+        if let Some(private) = Self::get_private_profile(env.clone(), employee.clone()) {
+            return Self::private_tier(&env, &private);
+        }
+        match Self::get_profile(env.clone(), employee) {
+            Some(profile) => Self::current_breakdown(&env, &profile).risk_tier,
+            None => 5, // Default to highest risk if no profile
+        }
+    }
+    
+    /// Checks if employee has a complete profile
+    pub fn has_profile(env: Env, employee: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Profile(employee.clone()))
+            || env.storage().persistent().has(&DataKey::PrivateProfile(employee))
+    }
+    
+    /// Internal function to get the codes of all registered sectors
+    fn get_sector_codes(env: &Env) -> Vec<Bytes> {
+        env.storage().persistent()
+            .get(&DataKey::SectorCodes)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+    
+    /// Internal function to store a profile and append it to the employee's history
+    fn store_profile(env: &Env, profile: &WorkProfile, changed_by: &Address) {
+        let employee = profile.employee.clone();
+        env.storage().persistent().set(&DataKey::Profile(employee.clone()), profile);
+        
+        let version = Self::get_profile_version_count(env.clone(), employee.clone());
+        let entry = ProfileVersion {
+            version,
+            profile: profile.clone(),
+            changed_by: changed_by.clone(),
+            changed_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&DataKey::ProfileVersion(employee.clone(), version), &entry);
+        env.storage().persistent().set(&DataKey::ProfileVersionCount(employee), &(version + 1));
+    }
+    
+    /// Internal function to score a profile's work and credit history under a model
+    fn rescore(env: &Env, profile: &mut WorkProfile, model: &ScoringModel) {
+        let breakdown = Self::profile_breakdown(env, model, profile);
+        profile.risk_score = breakdown.risk_score;
+        profile.risk_tier = breakdown.risk_tier;
+        profile.model_version = model.version;
+        profile.updated_at = env.ledger().timestamp();
+    }
+    
+    /// Internal function to break down a profile's score as of now under the model that scored it
+    /// Tenure grows with elapsed ledger time up to the expiry, after which profiles are degraded.
+    fn current_breakdown(env: &Env, profile: &WorkProfile) -> ScoreBreakdown {
+        let now = env.ledger().timestamp();
+        let mut current = profile.clone();
+        let elapsed_months = now.min(profile.expires_at).saturating_sub(profile.tenure_recorded_at) / SECONDS_PER_MONTH;
+        current.current_job_duration = profile.current_job_duration.saturating_add(elapsed_months as u32);
+        
+        let model = Self::get_scoring_model_version(env.clone(), profile.model_version);
+        let mut breakdown = Self::profile_breakdown(env, &model, &current);
+        breakdown.disputed = Self::get_open_dispute_count(env, &profile.employee) > 0;
+        if now > profile.expires_at {
+            breakdown.stale = true;
+            breakdown.risk_tier = breakdown.risk_tier.max(STALE_TIER_FLOOR);
+        }
+        breakdown
+    }
+    
+    /// Internal function to add income stability, credit history and sector to a profile's work history breakdown
+    /// Each factor only counts under models that score it. Unverified profiles are capped at a conservative tier.
+    fn profile_breakdown(env: &Env, model: &ScoringModel, profile: &WorkProfile) -> ScoreBreakdown {
+        let mut breakdown = Self::work_breakdown(model, profile.years_experience, profile.current_job_duration, profile.job_changes);
+        breakdown.income_points = Self::income_points(model, &profile.income);
+        if model.scores_credit_history {
+            let history = Self::get_credit_history(env.clone(), profile.employee.clone());
+            breakdown.credit_adjustment = Self::calculate_credit_adjustment(history);
+        }
+        if model.scores_sector {
+            breakdown.sector_adjustment = profile.sector_adjustment;
         }
+        breakdown.risk_score = (breakdown.risk_score as i32 + breakdown.income_points + breakdown.credit_adjustment + breakdown.sector_adjustment)
+            .clamp(0, 100) as u32;
+        
+        let tier = Self::model_tier(model, breakdown.risk_score);
+        breakdown.capped_unverified = !profile.verified && tier < UNVERIFIED_TIER_FLOOR;
+        breakdown.risk_tier = if profile.verified { tier } else { tier.max(UNVERIFIED_TIER_FLOOR) };
+        breakdown.next_tier_score = Self::next_tier_score(model, tier);
+        breakdown
+    }
+    
+    /// Internal function to break down the work history score under a model
+    fn work_breakdown(model: &ScoringModel, years_experience: u32, current_job_duration: u32, job_changes: u32) -> ScoreBreakdown {
+        // Years of experience: first bracket reached, the one above it is the next step
+        let experience_index = model.experience_brackets.iter().position(|bracket| years_experience >= bracket.threshold);
+        let experience_points = experience_index.map_or(0, |i| model.experience_brackets.get_unchecked(i as u32).points);
+        let next_experience_threshold = Self::next_threshold(&model.experience_brackets, experience_index);
+        
+        // Current job duration: first bracket reached, the one above it is the next step
+        let tenure_index = model.tenure_brackets.iter().position(|bracket| current_job_duration >= bracket.threshold);
+        let tenure_points = tenure_index.map_or(0, |i| model.tenure_brackets.get_unchecked(i as u32).points);
+        let next_tenure_threshold = Self::next_threshold(&model.tenure_brackets, tenure_index);
+        
+        // Job changes: bonus within a bracket, penalty for each change beyond the last one
+        let job_change_points = match model.job_change_brackets.iter().find(|bracket| job_changes <= bracket.threshold) {
+            Some(bracket) => bracket.points as i32,
+            None => {
+                let allowed = model.job_change_brackets.last().map_or(0, |bracket| bracket.threshold);
+                -((job_changes - allowed).saturating_mul(model.job_change_penalty).min(i32::MAX as u32) as i32)
+            },
+        };
         
         // Ensure score is between 0 and 100
-        if score > 100 {
-            100
+        let risk_score = (model.base_score as i64 + experience_points as i64 + tenure_points as i64 + job_change_points as i64)
+            .clamp(0, 100) as u32;
+        let risk_tier = Self::model_tier(model, risk_score);
+        
+        ScoreBreakdown {
+            model_version: model.version,
+            base_score: model.base_score,
+            experience_points,
+            tenure_points,
+            job_change_points,
+            income_points: 0,
+            credit_adjustment: 0,
+            sector_adjustment: 0,
+            risk_score,
+            risk_tier,
+            capped_unverified: false,
+            stale: false,
+            disputed: false,
+            next_tier_score: Self::next_tier_score(model, risk_tier),
+            next_experience_threshold,
+            next_tenure_threshold,
+        }
+    }
+    
+    /// Internal function to get the number of open disputes for an employee
+    fn get_open_dispute_count(env: &Env, employee: &Address) -> u32 {
+        env.storage().persistent().get(&DataKey::OpenDisputes(employee.clone())).unwrap_or(0)
+    }
+    
+    /// Internal function to check if a disputed field is a profile value rather than a credit history count
+    fn is_profile_field(field: DisputeField) -> bool {
+        matches!(field, DisputeField::YearsExperience | DisputeField::CurrentJobDuration | DisputeField::JobChanges)
+    }
+    
+    /// Internal function to write an upheld dispute's corrected value and rescore the profile
+    fn apply_correction(env: &Env, employee: &Address, field: DisputeField, value: u32, arbiter: &Address) {
+        let mut history = Self::get_credit_history(env.clone(), employee.clone());
+        let mut profile = Self::get_profile(env.clone(), employee.clone());
+        
+        match field {
+            DisputeField::YearsExperience => profile.as_mut().expect("Profile not found").years_experience = value,
+            DisputeField::CurrentJobDuration => {
+                let profile = profile.as_mut().expect("Profile not found");
+                profile.current_job_duration = value;
+                profile.tenure_recorded_at = env.ledger().timestamp();
+            },
+            DisputeField::JobChanges => profile.as_mut().expect("Profile not found").job_changes = value,
+            DisputeField::OnTimePayments => history.on_time_payments = value,
+            DisputeField::LatePayments => history.late_payments = value,
+            DisputeField::Defaults => history.defaults = value,
+            DisputeField::Payoffs => history.payoffs = value,
+        }
+        env.storage().persistent().set(&DataKey::CreditHistory(employee.clone()), &history);
+        
+        // Profiles are rescored under the model they were scored with, like credit events
+        if let Some(mut profile) = profile {
+            let model = Self::get_scoring_model_version(env.clone(), profile.model_version);
+            Self::rescore(env, &mut profile, &model);
+            Self::store_profile(env, &profile, arbiter);
+        }
+    }
+    
+    /// Internal function to get a private profile's tier from its committed score and current credit history
+    fn private_tier(env: &Env, private: &PrivateProfile) -> u32 {
+        let model = Self::get_scoring_model_version(env.clone(), private.model_version);
+        let credit_adjustment = if model.scores_credit_history {
+            Self::calculate_credit_adjustment(Self::get_credit_history(env.clone(), private.employee.clone()))
+        } else {
+            0
+        };
+        let risk_score = (private.work_score as i32 + credit_adjustment).clamp(0, 100) as u32;
+        let tier = Self::model_tier(&model, risk_score);
+        
+        if env.ledger().timestamp() > private.expires_at {
+            tier.max(STALE_TIER_FLOOR)
         } else {
-            score
+            tier
+        }
+    }
+    
+    /// Internal function to get a field's position in the commitment
+    fn field_index(field: ProfileField) -> u32 {
+        match field {
+            ProfileField::YearsExperience => 0,
+            ProfileField::CurrentJobDuration => 1,
+            ProfileField::JobChanges => 2,
+            ProfileField::Sector => 3,
+        }
+    }
+    
+    /// Internal function to find an opened field's value
+    fn opened_value(openings: &Vec<FieldOpening>, field: ProfileField) -> Option<Bytes> {
+        openings.iter().find(|opening| opening.field == field).map(|opening| opening.value)
+    }
+    
+    /// Internal function to decode a big-endian u32 field value, if well formed
+    fn decode_u32(value: &Bytes) -> Option<u32> {
+        if value.len() != 4 {
+            return None;
         }
+        let mut buf = [0u8; 4];
+        value.copy_into_slice(&mut buf);
+        Some(u32::from_be_bytes(buf))
     }
     
-    /// Gets risk tier based on score
-    pub fn get_risk_tier(risk_score: u32) -> u32 {
-        if risk_score >= 80 {
-            1 // Excellent
-        } else if risk_score >= 65 {
-            2 // Good
-        } else if risk_score >= 50 {
-            3 // Fair
-        } else if risk_score >= 35 {
-            4 // Poor
+    /// Internal function to score income stability under a model
+    fn income_points(model: &ScoringModel, income: &VerifiedIncome) -> i32 {
+        let stability_points = model.stability_brackets.iter()
+            .find(|bracket| income.months_streamed >= bracket.threshold)
+            .map_or(0, |bracket| bracket.points as i32);
+        let interruption_penalty = income.interruptions.saturating_mul(model.interruption_penalty).min(100) as i32;
+        stability_points - interruption_penalty
+    }
+    
+    /// Internal function to snapshot verified income, or none if salary streaming isn't configured
+    fn income_snapshot(env: &Env, employee: &Address) -> VerifiedIncome {
+        if env.storage().persistent().has(&DataKey::StreamingContract) {
+            Self::get_verified_income(env.clone(), employee.clone())
         } else {
-            5 // High Risk
+            Self::no_income()
         }
     }
     
-    /// Gets risk tier directly for an employee
-    pub fn get_employee_risk_tier(env: Env, employee: Address) -> u32 {
-        match Self::get_profile(env, employee) {
-            Some(profile) => profile.risk_tier,
-            None => 5, // Default to highest risk if no profile
+    /// Internal function to build an empty income snapshot
+    fn no_income() -> VerifiedIncome {
+        VerifiedIncome {
+            monthly_income: 0,
+            active_streams: 0,
+            months_streamed: 0,
+            interruptions: 0,
         }
     }
     
-    /// Checks if employee has a complete profile
-    pub fn has_profile(env: Env, employee: Address) -> bool {
-        env.storage().persistent().has(&DataKey::Profile(employee))
+    /// Internal function to get the threshold of the bracket above the one reached, or the lowest if none was reached
+    fn next_threshold(brackets: &Vec<ScoreBracket>, reached: Option<usize>) -> Option<u32> {
+        let next = match reached {
+            Some(0) => None,
+            Some(i) => brackets.get(i as u32 - 1),
+            None => brackets.last(),
+        };
+        next.map(|bracket| bracket.threshold)
+    }
+    
+    /// Internal function to get the minimum score of the tier above, if any
+    fn next_tier_score(model: &ScoringModel, risk_tier: u32) -> Option<u32> {
+        if risk_tier <= 1 {
+            None
+        } else {
+            model.tier_cutoffs.get(risk_tier - 2)
+        }
+    }
+    
+    /// Internal function to get the tier for a score under a model's cutoffs
+    fn model_tier(model: &ScoringModel, risk_score: u32) -> u32 {
+        match model.tier_cutoffs.iter().position(|cutoff| risk_score >= cutoff) {
+            Some(index) => index as u32 + 1,
+            None => 5, // High Risk
+        }
+    }
+    
+    /// Internal function to build the built-in scoring model
+    /// Profiles scored under it keep their scores, so new factors are only added through published versions.
+    fn default_model(env: &Env) -> ScoringModel {
+        let bracket = |threshold, points| ScoreBracket { threshold, points };
+        ScoringModel {
+            version: DEFAULT_MODEL_VERSION,
+            base_score: 50,
+            experience_brackets: vec![env, bracket(8, 40), bracket(5, 30), bracket(3, 20), bracket(1, 10)],
+            tenure_brackets: vec![env, bracket(24, 30), bracket(12, 20), bracket(6, 10)],
+            job_change_brackets: vec![env, bracket(1, 20), bracket(2, 10), bracket(3, 5)],
+            job_change_penalty: 5,
+            tier_cutoffs: vec![env, 80, 65, 50, 35], // Excellent, Good, Fair, Poor
+            stability_brackets: Vec::new(env), // Income stability, credit history and sector are scored from published models onwards
+            interruption_penalty: 0,
+            scores_credit_history: false,
+            scores_sector: false,
+        }
+    }
+    
+    /// Internal function to check if an address is the employee's active employer in salary streaming
+    fn is_current_employer(env: &Env, employer: &Address, employee: &Address) -> bool {
+        let streaming_contract: Option<Address> = env.storage().persistent().get(&DataKey::StreamingContract);
+        
+        match streaming_contract {
+            Some(streaming_contract) => matches!(
+                SalaryStreamingClient::new(env, &streaming_contract).try_get_employee_info(employee),
+                Ok(Ok(info)) if info.is_active && info.employer == *employer
+            ),
+            None => false,
+        }
+    }
+    
+    /// Internal function to check the caller is the stored admin
+    fn require_admin(env: &Env, admin: &Address) {
+        admin.require_auth();
+        
+        let stored_admin: Address = env.storage().persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized");
+        if stored_admin != *admin {
+            panic!("Unauthorized");
+        }
     }
 }
 
@@ -1,27 +1,125 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env, Bytes};
+use soroban_sdk::{testutils::{Address as _, Ledger as _}, vec, Env, Bytes, BytesN, String};
+
+mod mock_streaming {
+    use crate::{EmployeeInfo, EmploymentRecord, SalaryStream, StreamPauses};
+    use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Vec};
+
+    #[contracttype]
+    enum MockKey {
+        Info(Address),
+        History(Address),
+        Streams(Address),
+        Pauses(u32),
+    }
+
+    /// Minimal stand-in for the salary streaming contract's employee registry, history and streams
+    #[contract]
+    pub struct MockStreamingContract;
+
+    #[contractimpl]
+    impl MockStreamingContract {
+        pub fn set_employee(env: Env, employee: Address, employer: Address, start_date: u64, is_active: bool) {
+            let name = String::from_str(&env, "");
+            let info = EmployeeInfo {
+                address: employee.clone(),
+                name: name.clone(),
+                email: name.clone(),
+                phone: name.clone(),
+                position: name.clone(),
+                department: name,
+                employer: employer.clone(),
+                registration_time: 0,
+                start_date,
+                end_date: None,
+                is_active,
+            };
+            env.storage().persistent().set(&MockKey::Info(employee.clone()), &info);
+            
+            let mut history = Self::get_employment_history(env.clone(), employee.clone());
+            history.push_back(EmploymentRecord { employer, start_date, end_date: None });
+            env.storage().persistent().set(&MockKey::History(employee), &history);
+        }
+
+        pub fn rehire(env: Env, employee: Address, start_date: u64) {
+            // Like reactivation, the registered start date is kept and a new record starts
+            let info = Self::get_employee_info(env.clone(), employee.clone());
+            let mut history = Self::get_employment_history(env.clone(), employee.clone());
+            history.push_back(EmploymentRecord { employer: info.employer, start_date, end_date: None });
+            env.storage().persistent().set(&MockKey::History(employee), &history);
+        }
+
+        pub fn get_employee_info(env: Env, employee: Address) -> EmployeeInfo {
+            env.storage().persistent().get(&MockKey::Info(employee)).expect("Employee not found")
+        }
+
+        pub fn get_employment_history(env: Env, employee: Address) -> Vec<EmploymentRecord> {
+            env.storage().persistent().get(&MockKey::History(employee)).unwrap_or_else(|| Vec::new(&env))
+        }
+
+        pub fn set_stream(env: Env, stream: SalaryStream, pauses: StreamPauses) {
+            let mut streams = Self::get_employee_streams(env.clone(), stream.employee.clone());
+            match streams.iter().position(|existing| existing.id == stream.id) {
+                Some(index) => streams.set(index as u32, stream.clone()),
+                None => streams.push_back(stream.clone()),
+            }
+            env.storage().persistent().set(&MockKey::Streams(stream.employee), &streams);
+            env.storage().persistent().set(&MockKey::Pauses(stream.id), &pauses);
+        }
+
+        pub fn get_employee_streams(env: Env, employee: Address) -> Vec<SalaryStream> {
+            env.storage().persistent().get(&MockKey::Streams(employee)).unwrap_or_else(|| Vec::new(&env))
+        }
+
+        pub fn get_stream_pauses(env: Env, stream_id: u32) -> StreamPauses {
+            env.storage().persistent().get(&MockKey::Pauses(stream_id)).expect("Stream not found")
+        }
+    }
+}
+
+/// Registers the sectors used across tests with no risk adjustment
+fn register_sectors(env: &Env, client: &WorkProfileContractClient, admin: &Address) {
+    for (code, name) in [("Technology", "Technology"), ("Finance", "Finance"), ("Retail", "Retail")] {
+        client.register_sector(admin, &Bytes::from_slice(env, code.as_bytes()), &String::from_str(env, name), &0i32);
+    }
+}
+
+/// Publishes a model scoring credit history and sector adjustments on top of the default one
+fn publish_adjusted_model(client: &WorkProfileContractClient, admin: &Address) -> u32 {
+    let mut model = client.get_scoring_model();
+    model.scores_credit_history = true;
+    model.scores_sector = true;
+    client.set_scoring_model(admin, &model)
+}
 
 #[test]
 fn test_calculate_risk_score() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    
     // High experience, stable job, few changes
-    let score = WorkProfileContract::calculate_risk_score(10, 36, 1);
+    let score = client.calculate_risk_score(&10u32, &36u32, &1u32);
     assert!(score >= 80); // Should be tier 1
     
-    // Medium experience, moderate stability
-    let score = WorkProfileContract::calculate_risk_score(5, 12, 2);
-    assert!(score >= 65 && score < 80); // Should be tier 2
+    // Entry-level experience, six months in the job, three changes
+    // From a base of 50 the middle brackets already reach 100, so tier 2 takes the lowest ones:
+    // 50 + 10 (experience) + 10 (tenure) + 5 (job changes) = 75
+    let score = client.calculate_risk_score(&1u32, &6u32, &3u32);
+    assert!((65..80).contains(&score)); // Should be tier 2
     
-    // Low experience, short tenure, many changes
-    let score = WorkProfileContract::calculate_risk_score(1, 3, 5);
+    // No experience, short tenure, three changes over the last bracket
+    // 50 - 15 (job changes) = 35
+    let score = client.calculate_risk_score(&0u32, &3u32, &6u32);
     assert!(score < 50); // Should be tier 4 or 5
 }
 
 #[test]
 fn test_update_profile() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, WorkProfileContract);
+    let contract_id = env.register(WorkProfileContract, ());
     let client = WorkProfileContractClient::new(&env, &contract_id);
 
     let employee = Address::generate(&env);
@@ -29,7 +127,10 @@ fn test_update_profile() {
 
     env.mock_all_auths();
 
-    client.update_profile(&employee, &5u8, &18u8, &2u8, &sector);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.update_profile(&employee, &5u32, &18u32, &2u32, &sector);
     
     let profile = client.get_profile(&employee).unwrap();
     assert_eq!(profile.employee, employee);
@@ -43,17 +144,21 @@ fn test_update_profile() {
 
 #[test]
 fn test_risk_tiers() {
-    assert_eq!(WorkProfileContract::get_risk_tier(90), 1);
-    assert_eq!(WorkProfileContract::get_risk_tier(70), 2);
-    assert_eq!(WorkProfileContract::get_risk_tier(55), 3);
-    assert_eq!(WorkProfileContract::get_risk_tier(40), 4);
-    assert_eq!(WorkProfileContract::get_risk_tier(20), 5);
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    
+    assert_eq!(client.get_risk_tier(&90u32), 1);
+    assert_eq!(client.get_risk_tier(&70u32), 2);
+    assert_eq!(client.get_risk_tier(&55u32), 3);
+    assert_eq!(client.get_risk_tier(&40u32), 4);
+    assert_eq!(client.get_risk_tier(&20u32), 5);
 }
 
 #[test]
 fn test_has_profile() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, WorkProfileContract);
+    let contract_id = env.register(WorkProfileContract, ());
     let client = WorkProfileContractClient::new(&env, &contract_id);
 
     let employee = Address::generate(&env);
@@ -63,14 +168,608 @@ fn test_has_profile() {
     
     env.mock_all_auths();
     
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    
     // Create profile
     let sector = Bytes::from_slice(&env, b"Finance");
-    client.update_profile(&employee, &3u8, &6u8, &1u8, &sector);
+    client.update_profile(&employee, &3u32, &6u32, &1u32, &sector);
     
     // Now has profile
     assert!(client.has_profile(&employee));
     
     // Check risk tier
     let tier = client.get_employee_risk_tier(&employee);
-    assert!(tier >= 1 && tier <= 5);
+    assert!((1..=5).contains(&tier));
+}
+
+#[test]
+fn test_credit_history_improves_tier() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let lender = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Retail");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.update_profile(&employee, &1u32, &3u32, &5u32, &sector);
+    client.add_verifier(&admin, &admin);
+    client.attest_profile(&admin, &employee, &1u32, &3u32, &5u32, &sector);
+    assert_eq!(client.get_employee_risk_tier(&employee), 3);
+    
+    // Only authorized reporters can submit credit events
+    assert!(client.try_report_credit_event(&lender, &employee, &CreditEvent::OnTimePayment).is_err());
+    client.add_reporter(&admin, &lender);
+    
+    // Good repayment history lifts the tier
+    for _ in 0..5 {
+        client.report_credit_event(&lender, &employee, &CreditEvent::OnTimePayment);
+    }
+    client.report_credit_event(&lender, &employee, &CreditEvent::Payoff);
+    assert_eq!(client.get_credit_history(&employee).on_time_payments, 5);
+    
+    // The default model doesn't score credit history, so it counts once rescored under one that does
+    assert_eq!(client.get_employee_risk_tier(&employee), 3);
+    publish_adjusted_model(&client, &admin);
+    client.recompute_profile(&employee);
+    assert_eq!(client.get_employee_risk_tier(&employee), 2);
+    
+    // A default pulls it back down
+    client.report_credit_event(&lender, &employee, &CreditEvent::Default);
+    assert_eq!(client.get_employee_risk_tier(&employee), 4);
+}
+
+#[test]
+fn test_profile_attestation() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    let streaming_id = env.register(mock_streaming::MockStreamingContract, ());
+    let streaming = mock_streaming::MockStreamingContractClient::new(&env, &streaming_id);
+
+    let admin = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let other_employer = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Technology");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.set_streaming_contract(&admin, &streaming_id);
+    streaming.set_employee(&employee, &employer, &0u64, &true);
+    
+    // Self-reported profiles are held at a conservative tier
+    client.update_profile(&employee, &10u32, &36u32, &1u32, &sector);
+    let profile = client.get_profile(&employee).unwrap();
+    assert!(!profile.verified);
+    assert_eq!(profile.risk_tier, 3);
+    
+    // Only the current employer or a registered verifier can attest
+    assert!(client.try_attest_profile(&other_employer, &employee, &10u32, &36u32, &1u32, &sector).is_err());
+    assert!(client.try_attest_profile(&verifier, &employee, &10u32, &36u32, &1u32, &sector).is_err());
+    
+    // The attester commits to the values they checked
+    assert!(client.try_attest_profile(&employer, &employee, &12u32, &36u32, &1u32, &sector).is_err());
+    client.attest_profile(&employer, &employee, &10u32, &36u32, &1u32, &sector);
+    let profile = client.get_profile(&employee).unwrap();
+    assert!(profile.verified);
+    assert_eq!(profile.attested_by, Some(employer.clone()));
+    assert_eq!(profile.risk_tier, 1);
+    
+    // Updating values clears the attestation until a verifier signs again
+    client.update_profile(&employee, &10u32, &37u32, &1u32, &sector);
+    assert!(!client.is_profile_verified(&employee));
+    client.add_verifier(&admin, &verifier);
+    client.attest_profile(&verifier, &employee, &10u32, &37u32, &1u32, &sector);
+    assert!(client.is_profile_verified(&employee));
+    
+    // Former employers can no longer attest
+    streaming.set_employee(&employee, &employer, &0u64, &false);
+    client.update_profile(&employee, &10u32, &38u32, &1u32, &sector);
+    assert!(client.try_attest_profile(&employer, &employee, &10u32, &38u32, &1u32, &sector).is_err());
+}
+
+#[test]
+fn test_refresh_profile_from_employment_records() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    let streaming_id = env.register(mock_streaming::MockStreamingContract, ());
+    let streaming = mock_streaming::MockStreamingContractClient::new(&env, &streaming_id);
+
+    let admin = Address::generate(&env);
+    let first_employer = Address::generate(&env);
+    let second_employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Technology");
+    let month = 30 * 24 * 60 * 60u64;
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.set_streaming_contract(&admin, &streaming_id);
+    
+    // Employee overstates tenure and hides a job change
+    streaming.set_employee(&employee, &first_employer, &0u64, &false);
+    streaming.set_employee(&employee, &second_employer, &(6 * month), &true);
+    client.update_profile(&employee, &5u32, &36u32, &0u32, &sector);
+    
+    // Refresh replaces them with on-chain facts
+    env.ledger().with_mut(|li| li.timestamp = 10 * month);
+    client.refresh_profile(&employee);
+    let profile = client.get_profile(&employee).unwrap();
+    assert_eq!(profile.current_job_duration, 4);
+    assert_eq!(profile.job_changes, 1);
+    assert_eq!(profile.years_experience, 5);
+    assert_eq!(profile.risk_score, client.calculate_risk_score(&5u32, &4u32, &1u32));
+    
+    // Refreshing values that still match the records keeps the attestation and adds no history
+    client.attest_profile(&second_employer, &employee, &5u32, &4u32, &1u32, &sector);
+    let versions = client.get_profile_version_count(&employee);
+    env.ledger().with_mut(|li| li.timestamp = 11 * month);
+    client.refresh_profile(&employee);
+    assert_eq!(client.get_profile_version_count(&employee), versions);
+    assert!(client.get_profile(&employee).unwrap().verified);
+    
+    // Tenure restarts at rehire and the changed values lose their attestation
+    streaming.rehire(&employee, &(12 * month));
+    env.ledger().with_mut(|li| li.timestamp = 14 * month);
+    client.refresh_profile(&employee);
+    let profile = client.get_profile(&employee).unwrap();
+    assert_eq!(profile.current_job_duration, 2);
+    assert_eq!(profile.job_changes, 1);
+    assert!(!profile.verified);
+    assert_eq!(profile.attested_by, None);
+    assert_eq!(client.get_profile_version_count(&employee), versions + 1);
+}
+
+#[test]
+fn test_scoring_model_versions() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Technology");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.update_profile(&employee, &4u32, &12u32, &2u32, &sector);
+    let profile = client.get_profile(&employee).unwrap();
+    assert_eq!(profile.model_version, 1);
+    assert_eq!(profile.risk_score, 100);
+    
+    // Stricter model with a lower base score and higher cutoffs
+    let mut model = client.get_scoring_model();
+    model.base_score = 20;
+    model.tier_cutoffs = vec![&env, 90u32, 75, 60, 45];
+    let version = client.set_scoring_model(&admin, &model);
+    assert_eq!(version, 2);
+    assert_eq!(client.get_scoring_model().base_score, 20);
+    
+    // Existing scores stay on their model until recomputed
+    assert_eq!(client.get_profile(&employee).unwrap().risk_score, 100);
+    client.recompute_profile(&employee);
+    let profile = client.get_profile(&employee).unwrap();
+    assert_eq!(profile.model_version, 2);
+    assert_eq!(profile.risk_score, 70);
+    
+    // Recomputing an unchanged score adds no history
+    assert_eq!(client.get_profile_version_count(&employee), 2);
+    client.recompute_profile(&employee);
+    assert_eq!(client.get_profile_version_count(&employee), 2);
+    assert_eq!(client.get_risk_tier(&70u32), 3);
+    assert_eq!(client.get_scoring_model_version(&1u32).base_score, 50);
+    
+    // Invalid cutoffs are rejected
+    model.tier_cutoffs = vec![&env, 50u32, 60, 70, 80];
+    assert!(client.try_set_scoring_model(&admin, &model).is_err());
+}
+
+#[test]
+fn test_explain_score() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Retail");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.add_verifier(&admin, &admin);
+    client.update_profile(&employee, &1u32, &3u32, &5u32, &sector);
+    client.attest_profile(&admin, &employee, &1u32, &3u32, &5u32, &sector);
+    
+    let breakdown = client.explain_score(&employee);
+    assert_eq!(breakdown.base_score, 50);
+    assert_eq!(breakdown.experience_points, 10);
+    assert_eq!(breakdown.tenure_points, 0);
+    assert_eq!(breakdown.job_change_points, -10);
+    assert_eq!(breakdown.risk_score, client.get_profile(&employee).unwrap().risk_score);
+    assert_eq!(breakdown.risk_tier, 3);
+    
+    // Shows what it takes to move up
+    assert_eq!(breakdown.next_tier_score, Some(65));
+    assert_eq!(breakdown.next_experience_threshold, Some(3));
+    assert_eq!(breakdown.next_tenure_threshold, Some(6));
+    
+    // Strong but unattested profiles are reported as capped
+    client.update_profile(&employee, &10u32, &36u32, &1u32, &sector);
+    let breakdown = client.explain_score(&employee);
+    assert_eq!(breakdown.risk_score, 100);
+    assert!(breakdown.capped_unverified);
+    assert_eq!(breakdown.risk_tier, 3);
+    assert!(breakdown.next_tier_score.is_none());
+}
+
+#[test]
+fn test_profile_history_and_alerts() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Finance");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.add_verifier(&admin, &verifier);
+    
+    // Employee keeps hiding job changes
+    client.update_profile(&employee, &3u32, &6u32, &4u32, &sector);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.update_profile(&employee, &3u32, &6u32, &3u32, &sector);
+    assert!(!client.get_profile_alerts(&employee).suspicious);
+    client.attest_profile(&verifier, &employee, &3u32, &6u32, &3u32, &sector);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.update_profile(&employee, &3u32, &6u32, &1u32, &sector);
+    
+    let alerts = client.get_profile_alerts(&employee);
+    assert_eq!(alerts.job_change_decreases, 2);
+    assert!(alerts.suspicious);
+    
+    // Every version is kept with who changed it and when
+    assert_eq!(client.get_profile_version_count(&employee), 4);
+    let history = client.get_profile_history(&employee, &0u32, &10u32);
+    assert_eq!(history.len(), 4);
+    assert_eq!(history.get(0).unwrap().profile.job_changes, 4);
+    assert_eq!(history.get(2).unwrap().changed_by, verifier);
+    assert_eq!(history.get(3).unwrap().changed_at, 200);
+    assert!(history.get(3).unwrap().profile.risk_score > history.get(0).unwrap().profile.risk_score);
+    
+    // Paging from a cursor
+    let page = client.get_profile_history(&employee, &1u32, &2u32);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().version, 1);
+}
+
+#[test]
+fn test_sector_taxonomy() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let hospitality = Bytes::from_slice(&env, b"HOSP");
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    publish_adjusted_model(&client, &admin);
+    
+    // Any sector is accepted until a taxonomy is registered
+    client.update_profile(&employee, &3u32, &12u32, &2u32, &hospitality);
+    assert_eq!(client.explain_score(&employee).sector_adjustment, 0);
+    register_sectors(&env, &client, &admin);
+    
+    // Free-text sectors are rejected
+    assert!(client.try_update_profile(&employee, &3u32, &12u32, &2u32, &hospitality).is_err());
+    
+    // Volatile sector carries a penalty
+    client.register_sector(&admin, &hospitality, &String::from_str(&env, "Hospitality"), &-10i32);
+    assert_eq!(client.get_sectors().len(), 4);
+    client.update_profile(&employee, &3u32, &12u32, &2u32, &hospitality);
+    let breakdown = client.explain_score(&employee);
+    assert_eq!(breakdown.sector_adjustment, -10);
+    assert_eq!(breakdown.risk_score, client.calculate_risk_score(&3u32, &12u32, &2u32) - 10);
+    
+    // Changing the adjustment applies to profiles once they are recomputed
+    client.register_sector(&admin, &hospitality, &String::from_str(&env, "Hospitality"), &-20i32);
+    assert_eq!(client.explain_score(&employee).sector_adjustment, -10);
+    client.recompute_profile(&employee);
+    assert_eq!(client.explain_score(&employee).sector_adjustment, -20);
+    
+    // Retired sectors drop out of the dropdown and can't be chosen or re-registered
+    client.retire_sector(&admin, &hospitality);
+    assert_eq!(client.get_sectors().len(), 3);
+    assert!(client.try_update_profile(&employee, &3u32, &12u32, &2u32, &hospitality).is_err());
+    assert!(client.try_register_sector(&admin, &hospitality, &String::from_str(&env, "Hospitality"), &-10i32).is_err());
+    assert!(client.try_register_sector(&admin, &hospitality, &String::from_str(&env, "Hospitality"), &-60i32).is_err());
+    
+    // Reactivation is explicit
+    client.reactivate_sector(&admin, &hospitality);
+    assert_eq!(client.get_sectors().len(), 4);
+    client.update_profile(&employee, &3u32, &12u32, &2u32, &hospitality);
+}
+
+#[test]
+fn test_profile_tenure_growth_and_staleness() {
+    let env = Env::default();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let sector = Bytes::from_slice(&env, b"Technology");
+    let month = 30 * 24 * 60 * 60u64;
+
+    env.mock_all_auths();
+
+    client.initialize(&admin);
+    register_sectors(&env, &client, &admin);
+    client.add_verifier(&admin, &verifier);
+    client.set_max_profile_age(&admin, &(12 * month));
+    
+    // 55 points: tenure 10 at six months, one job change over the limit
+    client.update_profile(&employee, &0u32, &6u32, &4u32, &sector);
+    client.attest_profile(&verifier, &employee, &0u32, &6u32, &4u32, &sector);
+    assert_eq!(client.get_employee_risk_tier(&employee), 3);
+    assert_eq!(client.get_profile(&employee).unwrap().expires_at, 12 * month);
+    
+    // Six months later tenure has reached a year without any update
+    env.ledger().with_mut(|li| li.timestamp = 6 * month);
+    let breakdown = client.explain_score(&employee);
+    assert_eq!(breakdown.tenure_points, 20);
+    assert_eq!(client.get_employee_risk_tier(&employee), 2);
+    
+    // Past the expiry the profile degrades until re-attested
+    env.ledger().with_mut(|li| li.timestamp = 13 * month);
+    assert!(client.explain_score(&employee).stale);
+    assert_eq!(client.get_employee_risk_tier(&employee), 4);
+    assert!(!client.is_profile_verified(&employee));
+    
+    // Tenure stops growing at the expiry: eighteen months, not twenty-six
+    env.ledger().with_mut(|li| li.timestamp = 20 * month);
+    assert_eq!(client.explain_score(&employee).tenure_points, 20);
+    
+    client.attest_profile(&verifier, &employee, &0u32, &6u32, &4u32, &sector);
+    assert!(client.is_profile_verified(&employee));
+    assert_eq!(client.explain_score(&employee).tenure_points, 30);
+    assert_eq!(client.get_employee_risk_tier(&employee), 2);
+    
+    // Self-updates don't extend the attested expiry
+    env.ledger().with_mut(|li| li.timestamp = 21 * month);
+    client.update_profile(&employee, &1u32, &21u32, &4u32, &sector);
+    assert_eq!(client.get_profile(&employee).unwrap().expires_at, 32 * month);
+    env.ledger().with_mut(|li| li.timestamp = 33 * month);
+    assert!(client.explain_score(&employee).stale);
+}
+
+#[test]
+fn test_verified_income_stability() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    let streaming_id = env.register(mock_streaming::MockStreamingContract, ());
+    let streaming = mock_streaming::MockStreamingContractClient::new(&env, &streaming_id);
+    
+    let admin = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let employee = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_streaming_contract(&admin, &streaming_id);
+    register_sectors(&env, &client, &admin);
+    let tech = Bytes::from_slice(&env, b"Technology");
+    let month = 30 * 24 * 60 * 60u64;
+    
+    // Income stability is scored from version 2 onwards
+    let mut model = client.get_scoring_model();
+    assert_eq!(model.stability_brackets.len(), 0);
+    model.stability_brackets = vec![&env, ScoreBracket { threshold: 12, points: 10 }, ScoreBracket { threshold: 6, points: 5 }];
+    model.interruption_penalty = 2;
+    assert_eq!(client.set_scoring_model(&admin, &model), 2);
+    
+    // Stream paid since launch, paused once and resumed after a month
+    let mut stream = SalaryStream {
+        id: 1,
+        employer,
+        employee: employee.clone(),
+        total_amount: 24 * month as i128,
+        rate_per_second: 1,
+        start_time: 0,
+        duration_seconds: 24 * month,
+        withdrawn_amount: 0,
+        is_active: true,
+        is_paused: false,
+    };
+    let pauses = StreamPauses {
+        pause_count: 1,
+        paused_seconds: month / 2,
+        paused_at: None,
+        last_resumed_at: month,
+    };
+    streaming.set_stream(&stream, &pauses);
+    
+    env.ledger().with_mut(|li| li.timestamp = 8 * month);
+    let income = client.get_verified_income(&employee);
+    assert_eq!(income.monthly_income, month as i128);
+    assert_eq!(income.active_streams, 1);
+    assert_eq!(income.months_streamed, 7);
+    assert_eq!(income.interruptions, 1);
+    
+    // Work history alone: 50 + 10 (experience) - 5 (job changes) = 55
+    // Six months streamed (+5) less one interruption (-2)
+    client.update_profile(&employee, &1u32, &0u32, &4u32, &tech);
+    let breakdown = client.explain_score(&employee);
+    assert_eq!(breakdown.income_points, 3);
+    assert_eq!(client.get_profile(&employee).unwrap().risk_score, 58);
+    
+    // A year of uninterrupted streaming reaches the top bracket
+    env.ledger().with_mut(|li| li.timestamp = 14 * month);
+    client.update_profile(&employee, &1u32, &0u32, &4u32, &tech);
+    assert_eq!(client.explain_score(&employee).income_points, 8);
+    assert_eq!(client.get_profile(&employee).unwrap().risk_score, 63);
+    
+    // Paused streams don't count as income
+    stream.is_paused = true;
+    streaming.set_stream(&stream, &pauses);
+    let income = client.get_verified_income(&employee);
+    assert_eq!(income.monthly_income, 0);
+    assert_eq!(income.active_streams, 0);
+}
+
+#[test]
+fn test_private_profile_commitment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    
+    let admin = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let employee = Address::generate(&env);
+    client.initialize(&admin);
+    client.add_verifier(&admin, &verifier);
+    register_sectors(&env, &client, &admin);
+    publish_adjusted_model(&client, &admin);
+    
+    // 50 + 10 (experience) - 5 (job changes) = 55
+    let openings = vec![
+        &env,
+        FieldOpening { field: ProfileField::YearsExperience, value: Bytes::from_array(&env, &1u32.to_be_bytes()), salt: BytesN::from_array(&env, &[1; 32]) },
+        FieldOpening { field: ProfileField::CurrentJobDuration, value: Bytes::from_array(&env, &0u32.to_be_bytes()), salt: BytesN::from_array(&env, &[2; 32]) },
+        FieldOpening { field: ProfileField::JobChanges, value: Bytes::from_array(&env, &4u32.to_be_bytes()), salt: BytesN::from_array(&env, &[3; 32]) },
+        FieldOpening { field: ProfileField::Sector, value: Bytes::from_slice(&env, b"Technology"), salt: BytesN::from_array(&env, &[4; 32]) },
+    ];
+    let mut field_hashes = Vec::new(&env);
+    let mut preimage = Bytes::new(&env);
+    for opening in openings.iter() {
+        let hash = client.hash_field(&opening.value, &opening.salt);
+        preimage.append(&hash.clone().into());
+        field_hashes.push_back(hash);
+    }
+    let commitment: BytesN<32> = env.crypto().sha256(&preimage).to_bytes();
+    
+    client.commit_profile(&verifier, &employee, &commitment, &55u32, &0i32);
+    assert!(client.has_profile(&employee));
+    assert!(client.get_profile(&employee).is_none());
+    assert!(client.is_profile_verified(&employee));
+    assert_eq!(client.get_employee_risk_tier(&employee), 3);
+    
+    // Opening experience only is checked against its hash and leaves the score reachable
+    let experience = vec![&env, openings.get_unchecked(0)];
+    assert!(client.verify_opening(&employee, &field_hashes, &experience));
+    
+    // Wrong preimage is rejected
+    let mut forged = openings.get_unchecked(0);
+    forged.value = Bytes::from_array(&env, &9u32.to_be_bytes());
+    assert!(!client.verify_opening(&employee, &field_hashes, &vec![&env, forged]));
+    
+    // Opening every field checks the committed score exactly
+    assert!(client.verify_opening(&employee, &field_hashes, &openings));
+    
+    // An inflated score is caught once the numeric fields are open, even with the sector kept private
+    client.commit_profile(&verifier, &employee, &commitment, &70u32, &0i32);
+    assert!(client.verify_opening(&employee, &field_hashes, &experience));
+    let numeric = vec![&env, openings.get_unchecked(0), openings.get_unchecked(1), openings.get_unchecked(2)];
+    assert!(!client.verify_opening(&employee, &field_hashes, &numeric));
+    assert!(!client.verify_opening(&employee, &field_hashes, &openings));
+    
+    // The committed sector adjustment must belong to a registered sector
+    assert!(client.try_commit_profile(&verifier, &employee, &commitment, &55u32, &-10i32).is_err());
+    
+    // An adjustment taken from another sector is caught once the sector is opened
+    client.register_sector(&admin, &Bytes::from_slice(&env, b"Retail"), &String::from_str(&env, "Retail"), &-10i32);
+    client.commit_profile(&verifier, &employee, &commitment, &45u32, &-10i32);
+    assert!(client.verify_opening(&employee, &field_hashes, &numeric));
+    assert!(!client.verify_opening(&employee, &field_hashes, &openings));
+    
+    // Later changes to the taxonomy don't affect a committed profile
+    client.commit_profile(&verifier, &employee, &commitment, &55u32, &0i32);
+    client.register_sector(&admin, &Bytes::from_slice(&env, b"Technology"), &String::from_str(&env, "Technology"), &-10i32);
+    assert!(client.verify_opening(&employee, &field_hashes, &openings));
+    
+    // Only verifiers and employers can commit
+    let stranger = Address::generate(&env);
+    assert!(client.try_commit_profile(&stranger, &employee, &commitment, &55u32, &0i32).is_err());
+}
+
+#[test]
+fn test_profile_disputes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(WorkProfileContract, ());
+    let client = WorkProfileContractClient::new(&env, &contract_id);
+    
+    let admin = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let reporter = Address::generate(&env);
+    let employee = Address::generate(&env);
+    client.initialize(&admin);
+    client.add_arbiter(&admin, &arbiter);
+    client.add_reporter(&admin, &reporter);
+    register_sectors(&env, &client, &admin);
+    publish_adjusted_model(&client, &admin);
+    
+    // 50 + 10 (experience) - 5 (job changes) = 55, less 5 for the late payment
+    client.update_profile(&employee, &1u32, &0u32, &4u32, &Bytes::from_slice(&env, b"Technology"));
+    client.report_credit_event(&reporter, &employee, &CreditEvent::LatePayment);
+    assert_eq!(client.get_profile(&employee).unwrap().risk_score, 50);
+    
+    let reason = String::from_str(&env, "Payment was made on time");
+    let dispute_id = client.open_dispute(&employee, &DisputeField::LatePayments, &reason);
+    assert!(client.is_profile_disputed(&employee));
+    assert!(client.explain_score(&employee).disputed);
+    assert!(client.try_open_dispute(&employee, &DisputeField::LatePayments, &reason).is_err());
+    
+    // Only the admin or an arbiter can resolve
+    let stranger = Address::generate(&env);
+    assert!(client.try_resolve_dispute(&stranger, &dispute_id, &Some(0u32)).is_err());
+    
+    client.resolve_dispute(&arbiter, &dispute_id, &Some(0u32));
+    let dispute = client.get_dispute(&dispute_id);
+    assert!(dispute.status == DisputeStatus::Corrected);
+    assert_eq!(dispute.resolved_by, Some(arbiter.clone()));
+    assert_eq!(dispute.corrected_value, Some(0));
+    assert_eq!(client.get_credit_history(&employee).late_payments, 0);
+    assert_eq!(client.get_profile(&employee).unwrap().risk_score, 55);
+    assert!(!client.is_profile_disputed(&employee));
+    assert!(client.try_resolve_dispute(&arbiter, &dispute_id, &None).is_err());
+    
+    // Rejected disputes leave the profile unchanged
+    let reason = String::from_str(&env, "Only changed jobs twice");
+    let dispute_id = client.open_dispute(&employee, &DisputeField::JobChanges, &reason);
+    client.resolve_dispute(&admin, &dispute_id, &None);
+    assert!(client.get_dispute(&dispute_id).status == DisputeStatus::Rejected);
+    assert_eq!(client.get_profile(&employee).unwrap().job_changes, 4);
+    assert_eq!(client.get_employee_disputes(&employee).len(), 2);
+    
+    // Profile values can't be disputed without a profile
+    let newcomer = Address::generate(&env);
+    assert!(client.try_open_dispute(&newcomer, &DisputeField::CurrentJobDuration, &reason).is_err());
 }